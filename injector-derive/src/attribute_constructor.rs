@@ -1,14 +1,21 @@
-use proc_macro2::{Ident, TokenStream};
-use quote::quote;
-use syn::{FnArg, ItemFn, ReturnType, Type, TypePath};
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{ToTokens, quote};
+use syn::{FnArg, ImplItem, Item, ItemImpl, ReturnType, Signature, Type, TypePath};
 
 use crate::utils::{self, DependentType, Namespace, strip_lifetimes};
 
 pub struct ConstructorAttributeInputs {
     body_verbatim: TokenStream,
-    constructor_name: Ident,
+    constructors: Vec<Constructor>,
+}
+
+struct Constructor {
+    // Either the bare function name, or `<Type>::name` for associated functions.
+    constructor_path: TokenStream,
     ns: Namespace,
-    output_type: TypePath,
+    // More than one output type means the constructor returns a tuple, and each element of it
+    // becomes its own component.
+    output_types: Vec<TypePath>,
     inputs: Vec<FnArg>,
 }
 
@@ -23,23 +30,166 @@ impl ConstructorAttributeInputs {
                 "#[constructor] takes no arguments",
             ));
         }
-        let item = syn::parse::<ItemFn>(body_inputs.clone())?;
 
-        let constructor_name = item.sig.ident;
-        let ns = Namespace::from_fn_name(&constructor_name);
-        let output_type = Self::get_output_type(item.sig.output)?;
-        let inputs = item.sig.inputs.into_iter().collect();
+        match syn::parse::<Item>(body_inputs.clone())? {
+            Item::Fn(item) => {
+                let constructor_name = &item.sig.ident;
+                let ns = Namespace::from_fn_name(constructor_name);
+                let constructor =
+                    Constructor::from_signature(quote!(#constructor_name), ns, item.sig, None)?;
+
+                Ok(ConstructorAttributeInputs {
+                    body_verbatim: body_inputs.into(),
+                    constructors: vec![constructor],
+                })
+            }
+            Item::Impl(item) => Self::from_impl(item),
+            other => Err(syn::Error::new_spanned(
+                other,
+                "#[constructor] must be applied to a function or an impl block",
+            )),
+        }
+    }
+
+    /// Associated functions can't register themselves, as nothing generated inside an impl block
+    /// can name `Self`. So instead the impl block is annotated, and it registers every function
+    /// inside it that is also marked with `#[constructor]`.
+    fn from_impl(mut item: ItemImpl) -> syn::Result<Self> {
+        if let Some((_, trait_, _)) = &item.trait_ {
+            return Err(syn::Error::new_spanned(
+                trait_,
+                "#[constructor] can only be applied to inherent impl blocks",
+            ));
+        }
+        let Type::Path(self_type) = &*item.self_ty else {
+            return Err(syn::Error::new_spanned(
+                &item.self_ty,
+                "Only plain types can be injected",
+            ));
+        };
+        let self_type = self_type.clone();
+        let mut static_self_type = self_type.clone();
+        strip_lifetimes(&mut static_self_type.path);
+        let type_name = &self_type
+            .path
+            .segments
+            .last()
+            .expect("type paths are never empty")
+            .ident;
+
+        let mut constructors = Vec::new();
+        for impl_item in item.items.iter_mut() {
+            let ImplItem::Fn(function) = impl_item else {
+                continue;
+            };
+            let before = function.attrs.len();
+            function
+                .attrs
+                .retain(|attr| !attr.path().is_ident("constructor"));
+            if function.attrs.len() == before {
+                continue;
+            }
+
+            let constructor_name = &function.sig.ident;
+            let ns =
+                Namespace::from_type_name(type_name).with_suffix(&constructor_name.to_string());
+            constructors.push(Constructor::from_signature(
+                quote!(<#static_self_type>::#constructor_name),
+                ns,
+                function.sig.clone(),
+                Some(&self_type),
+            )?);
+        }
+
+        if constructors.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &item.self_ty,
+                "Mark the constructor inside this impl block with #[constructor]",
+            ));
+        }
 
         Ok(ConstructorAttributeInputs {
-            body_verbatim: body_inputs.into(),
-            constructor_name,
+            body_verbatim: item.into_token_stream(),
+            constructors,
+        })
+    }
+
+    pub fn generate_code(self) -> syn::Result<proc_macro::TokenStream> {
+        let create_fns = self
+            .constructors
+            .iter()
+            .map(Constructor::get_create_fn)
+            .collect::<syn::Result<Vec<_>>>()?;
+        let create_metas = self
+            .constructors
+            .iter()
+            .map(Constructor::get_create_meta)
+            .collect::<syn::Result<Vec<_>>>()?;
+        let original = self.body_verbatim;
+
+        Ok(quote! {
+            #(#create_fns)*
+            #(#create_metas)*
+            #original
+        }
+        .into())
+    }
+}
+
+impl Constructor {
+    fn from_signature(
+        constructor_path: TokenStream,
+        ns: Namespace,
+        signature: Signature,
+        self_type: Option<&TypePath>,
+    ) -> syn::Result<Self> {
+        if let Some(receiver) = signature.receiver() {
+            return Err(syn::Error::new_spanned(
+                receiver,
+                "Constructors can't take self, there is nothing to call them on",
+            ));
+        }
+        if self_type.is_none()
+            && let Some(input) = signature
+                .inputs
+                .iter()
+                .find(|input| mentions_self(input.to_token_stream()))
+        {
+            return Err(syn::Error::new_spanned(
+                input,
+                "Constructors using Self must be inside an impl block that is also marked with #[constructor]",
+            ));
+        }
+        let mut output_types = Self::get_output_types(signature.output)?;
+        for output_type in output_types.iter_mut() {
+            if !output_type.path.is_ident("Self") {
+                if mentions_self(output_type.to_token_stream()) {
+                    return Err(syn::Error::new_spanned(
+                        output_type,
+                        "Constructors must return Self itself, not a type that wraps it",
+                    ));
+                }
+                continue;
+            }
+            let Some(self_type) = self_type else {
+                return Err(syn::Error::new_spanned(
+                    output_type,
+                    "Constructors returning Self must be inside an impl block that is also marked with #[constructor]",
+                ));
+            };
+            *output_type = self_type.clone();
+        }
+        let inputs = signature.inputs.into_iter().collect();
+
+        Ok(Constructor {
+            constructor_path,
             ns,
-            output_type,
+            output_types,
             inputs,
         })
     }
 
-    fn get_output_type(output: ReturnType) -> syn::Result<TypePath> {
+    fn get_output_types(output: ReturnType) -> syn::Result<Vec<TypePath>> {
         let ReturnType::Type(_, inner) = output else {
             return Err(syn::Error::new_spanned(
                 output,
@@ -47,30 +197,44 @@ impl ConstructorAttributeInputs {
             ));
         };
         match *inner {
-            Type::Path(path) => Ok(path),
+            Type::Path(path) => Ok(vec![path]),
+            Type::Tuple(tuple) if tuple.elems.is_empty() => Err(syn::Error::new_spanned(
+                tuple,
+                "Constructors must return the type they create",
+            )),
+            Type::Tuple(tuple) if tuple.elems.len() == 1 => Err(syn::Error::new_spanned(
+                tuple,
+                "Constructors creating a single type must return it directly, not in a tuple",
+            )),
+            Type::Tuple(tuple) => tuple
+                .elems
+                .into_iter()
+                .map(|elem| match elem {
+                    Type::Path(path) => Ok(path),
+                    other => Err(syn::Error::new_spanned(
+                        other,
+                        "Only plain types can be injected",
+                    )),
+                })
+                .collect(),
             other => Err(syn::Error::new_spanned(
                 other,
                 "Only plain types can be injected",
             )),
         }
     }
-    pub fn generate_code(self) -> syn::Result<proc_macro::TokenStream> {
-        let create_fn = self.get_create_fn()?;
-        let create_meta = self.get_create_meta()?;
-        let original = self.body_verbatim;
 
-        Ok(quote! {
-            #create_fn
-            #create_meta
-            #original
+    fn static_output_types(&self) -> Vec<TypePath> {
+        let mut output_types = self.output_types.clone();
+        for output_type in &mut output_types {
+            strip_lifetimes(&mut output_type.path);
         }
-        .into())
+        output_types
     }
 
     fn get_create_fn(&self) -> syn::Result<TokenStream> {
-        let constructor_name = &self.constructor_name;
-        let mut output_type = self.output_type.clone();
-        strip_lifetimes(&mut output_type.path);
+        let constructor_path = &self.constructor_path;
+        let output_types = self.static_output_types();
         let create_fn_name = self.ns.name_of_create_fn();
         let params = self
             .inputs
@@ -78,21 +242,53 @@ impl ConstructorAttributeInputs {
             .map(|input| DependentType::from_fn_arg(input).map(|dep| dep.quote_get_call()))
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(quote! {
-            unsafe fn #create_fn_name(injector: &::injector::Injector) -> ::std::boxed::Box<dyn ::std::any::Any> {
-                let constructed = #constructor_name(#(#params),*);
+        let constructed = match output_types.as_slice() {
+            [output_type] => quote! {
+                let constructed = #constructor_path(#(#params),*);
                 ::std::boxed::Box::new(unsafe {
                     <#output_type as ::injector::Injectable>::upcast(constructed)
                 })
+            },
+            output_types => {
+                let elements = (0..output_types.len())
+                    .map(|i| Ident::new(&format!("element_{}", i), Span::call_site()))
+                    .collect::<Vec<_>>();
+                quote! {
+                    let (#(#elements),*) = #constructor_path(#(#params),*);
+                    ::std::boxed::Box::new(::injector::derive_api::Components(::std::vec![
+                        #(::std::boxed::Box::new(unsafe {
+                            <#output_types as ::injector::Injectable>::upcast(#elements)
+                        })),*
+                    ]))
+                }
+            }
+        };
+
+        Ok(quote! {
+            unsafe fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                ::std::result::Result::Ok({ #constructed })
             }
         })
     }
 
     fn get_create_meta(&self) -> syn::Result<TokenStream> {
-        let mut static_type = self.output_type.clone();
-        strip_lifetimes(&mut static_type.path);
+        let static_types = self
+            .static_output_types()
+            .into_iter()
+            .map(ToTokens::into_token_stream)
+            .collect::<Vec<_>>();
         let deps = self.inputs.iter().map(DependentType::from_fn_arg);
 
-        utils::quote_inject_meta(static_type, &self.ns, deps)
+        let (deps, collections) = utils::quote_dependencies(deps)?;
+
+        utils::quote_inject_meta_for_components(&static_types, &self.ns, deps, collections)
     }
 }
+
+fn mentions_self(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "Self",
+        TokenTree::Group(group) => mentions_self(group.stream()),
+        _ => false,
+    })
+}
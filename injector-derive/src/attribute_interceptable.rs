@@ -0,0 +1,50 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, ItemTrait, LitStr, TraitItem};
+
+pub struct InterceptableAttributeInputs {
+    body_verbatim: TokenStream,
+    trait_: ItemTrait,
+}
+
+impl InterceptableAttributeInputs {
+    pub fn from_input(
+        attr_inputs: proc_macro::TokenStream,
+        body_inputs: proc_macro::TokenStream,
+    ) -> syn::Result<Self> {
+        if !attr_inputs.is_empty() {
+            return Err(syn::Error::new_spanned(
+                TokenStream::from(attr_inputs),
+                "Expected #[interceptable]",
+            ));
+        }
+        let trait_ = syn::parse::<ItemTrait>(body_inputs.clone())?;
+
+        Ok(InterceptableAttributeInputs {
+            body_verbatim: body_inputs.into(),
+            trait_,
+        })
+    }
+
+    pub fn generate_code(self) -> syn::Result<proc_macro::TokenStream> {
+        let name: &Ident = &self.trait_.ident;
+        let (impl_generics, type_generics, where_clause) = self.trait_.generics.split_for_impl();
+        let methods = self.trait_.items.iter().filter_map(|item| match item {
+            TraitItem::Fn(method) => Some(LitStr::new(
+                &method.sig.ident.to_string(),
+                method.sig.ident.span(),
+            )),
+            _ => None,
+        });
+        let original = self.body_verbatim;
+
+        Ok(quote! {
+            #original
+
+            impl #impl_generics ::injector::derive_api::Interceptable for dyn #name #type_generics #where_clause {
+                const METHODS: &'static [&'static str] = &[#(#methods),*];
+            }
+        }
+        .into())
+    }
+}
@@ -0,0 +1,241 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{
+    FnArg, GenericArgument, ItemFn, PathArguments, ReturnType, Type, TypeTraitObject,
+};
+
+use crate::utils::{self, BindingOptions, DependentType, Namespace, TraitBounds};
+
+pub struct ProvidesAttributeInputs {
+    body_verbatim: TokenStream,
+    provider_name: Ident,
+    ns: Namespace,
+    output: ProvidedType,
+    is_multi_binding: bool,
+    inputs: Vec<FnArg>,
+}
+
+enum ProvidedType {
+    /// Any `'static` type, which gets stored as is.
+    Foreign(Type),
+    /// A tuple of `'static` types, each of which gets stored as is.
+    Tuple(Vec<Type>),
+    /// A `Box<dyn Trait>`, which gets stored and then bound to `dyn Trait`.
+    BoxedTraitObject(TraitBounds),
+}
+
+impl ProvidesAttributeInputs {
+    pub fn from_input(
+        attr_inputs: proc_macro::TokenStream,
+        body_inputs: proc_macro::TokenStream,
+    ) -> syn::Result<Self> {
+        let is_multi_binding = Self::is_multi_binding(attr_inputs)?;
+        let item = syn::parse::<ItemFn>(body_inputs.clone())?;
+
+        let provider_name = item.sig.ident;
+        let ns = Namespace::from_fn_name(&provider_name);
+        let ReturnType::Type(_, output_type) = item.sig.output else {
+            return Err(syn::Error::new_spanned(
+                item.sig.output,
+                "Providers must return the type they create",
+            ));
+        };
+        let output = ProvidedType::from_type(*output_type)?;
+        if is_multi_binding && !matches!(output, ProvidedType::BoxedTraitObject(_)) {
+            return Err(syn::Error::new_spanned(
+                &provider_name,
+                "#[provides(multi)] must return a Box<dyn Trait>",
+            ));
+        }
+        let inputs = item.sig.inputs.into_iter().collect();
+
+        Ok(ProvidesAttributeInputs {
+            body_verbatim: body_inputs.into(),
+            provider_name,
+            ns,
+            output,
+            is_multi_binding,
+            inputs,
+        })
+    }
+
+    fn is_multi_binding(attr_inputs: proc_macro::TokenStream) -> syn::Result<bool> {
+        if attr_inputs.is_empty() {
+            return Ok(false);
+        }
+
+        let attr_inputs = TokenStream::from(attr_inputs);
+        match syn::parse2::<Ident>(attr_inputs.clone()) {
+            Ok(ident) if ident == "multi" => Ok(true),
+            _ => Err(syn::Error::new_spanned(
+                attr_inputs,
+                "#[provides] only takes an optional `multi` argument",
+            )),
+        }
+    }
+
+    pub fn generate_code(self) -> syn::Result<proc_macro::TokenStream> {
+        let create_fn = self.get_create_fn()?;
+        let create_meta = self.get_create_meta()?;
+        let binding = self.get_binding();
+        let original = self.body_verbatim;
+
+        Ok(quote! {
+            #create_fn
+            #create_meta
+            #binding
+            #original
+        }
+        .into())
+    }
+
+    fn get_create_fn(&self) -> syn::Result<TokenStream> {
+        let provider_name = &self.provider_name;
+        let create_fn_name = self.ns.name_of_create_fn();
+        let params = self
+            .inputs
+            .iter()
+            .map(|input| DependentType::from_fn_arg(input).map(|dep| dep.quote_get_call()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Provided types are 'static, so unlike constructors there is no upcast to do here. Boxing
+        // the value as a `dyn Any` is what enforces that.
+        let provided = match &self.output {
+            ProvidedType::Foreign(output_type) => quote! {
+                let provided: #output_type = #provider_name(#(#params),*);
+            },
+            ProvidedType::Tuple(output_types) => {
+                let elements = (0..output_types.len())
+                    .map(|i| Ident::new(&format!("element_{}", i), Span::call_site()))
+                    .collect::<Vec<_>>();
+                quote! {
+                    let (#(#elements),*): (#(#output_types),*) = #provider_name(#(#params),*);
+                    let provided = ::injector::derive_api::Components(::std::vec![
+                        #(::std::boxed::Box::new(#elements)),*
+                    ]);
+                }
+            }
+            ProvidedType::BoxedTraitObject(trait_) => {
+                let holder = self.ns.name_of_provided_type();
+                quote! {
+                    let provided: ::std::boxed::Box<dyn #trait_> = #provider_name(#(#params),*);
+                    let provided = #holder(provided);
+                }
+            }
+        };
+
+        Ok(quote! {
+            fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                #provided
+                ::std::result::Result::Ok(::std::boxed::Box::new(provided))
+            }
+        })
+    }
+
+    fn get_create_meta(&self) -> syn::Result<TokenStream> {
+        let deps = self.inputs.iter().map(DependentType::from_fn_arg);
+
+        match &self.output {
+            ProvidedType::Foreign(output_type) => {
+                utils::quote_inject_meta(output_type, &self.ns, deps)
+            }
+            ProvidedType::Tuple(output_types) => {
+                let output_types = output_types
+                    .iter()
+                    .map(ToTokens::to_token_stream)
+                    .collect::<Vec<_>>();
+                let (deps, collections) = utils::quote_dependencies(deps)?;
+                utils::quote_inject_meta_for_components(
+                    &output_types,
+                    &self.ns,
+                    deps,
+                    collections,
+                )
+            }
+            ProvidedType::BoxedTraitObject(_) => {
+                let holder = self.ns.name_of_provided_type();
+                utils::quote_inject_meta(holder, &self.ns, deps)
+            }
+        }
+    }
+
+    fn get_binding(&self) -> TokenStream {
+        let ProvidedType::BoxedTraitObject(trait_) = &self.output else {
+            return quote!();
+        };
+
+        // Box<dyn Trait> is the same type for every provider of that trait, so each provider gets
+        // its own holder type to be stored under, which the binding then borrows from.
+        let binding_ns = self.ns.with_suffix("binding");
+        let create_fn_name = binding_ns.name_of_create_fn();
+        let holder = self.ns.name_of_provided_type();
+        let binding_meta = utils::quote_binding_meta(
+            trait_,
+            &binding_ns,
+            quote!(#holder),
+            &BindingOptions {
+                is_multi_binding: self.is_multi_binding,
+                ..BindingOptions::default()
+            },
+        );
+
+        quote! {
+            #[allow(non_camel_case_types)]
+            struct #holder(::std::boxed::Box<dyn #trait_>);
+
+            unsafe fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                let provided: &#holder = injector.get_foreign();
+                let trait_object: &(dyn #trait_) = &*provided.0;
+                let trait_object = unsafe {
+                    // SAFETY: See safety docs in BindingMeta::create
+                    ::std::mem::transmute::<&(dyn #trait_), &'static (dyn #trait_)>(trait_object)
+                };
+
+                ::std::result::Result::Ok(::std::boxed::Box::new(trait_object))
+            }
+            #binding_meta
+        }
+    }
+}
+
+impl ProvidedType {
+    fn from_type(ty: Type) -> syn::Result<Self> {
+        if let Some(trait_) = Self::boxed_trait_object(&ty) {
+            return Ok(ProvidedType::BoxedTraitObject(
+                DependentType::from_trait_object(trait_)?,
+            ));
+        }
+
+        match ty {
+            Type::Tuple(tuple) if tuple.elems.is_empty() => Err(syn::Error::new_spanned(
+                tuple,
+                "Providers must return the type they create",
+            )),
+            Type::Tuple(tuple) if tuple.elems.len() == 1 => Err(syn::Error::new_spanned(
+                tuple,
+                "Providers creating a single type must return it directly, not in a tuple",
+            )),
+            Type::Tuple(tuple) => Ok(ProvidedType::Tuple(tuple.elems.into_iter().collect())),
+            ty => Ok(ProvidedType::Foreign(ty)),
+        }
+    }
+
+    fn boxed_trait_object(ty: &Type) -> Option<&TypeTraitObject> {
+        let Type::Path(path) = ty else {
+            return None;
+        };
+        let last = path.path.segments.last()?;
+        if last.ident != "Box" {
+            return None;
+        }
+        let PathArguments::AngleBracketed(generics) = &last.arguments else {
+            return None;
+        };
+        match generics.args.first() {
+            Some(GenericArgument::Type(Type::TraitObject(trait_))) if generics.args.len() == 1 => {
+                Some(trait_)
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,74 +1,281 @@
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{ItemImpl, Path};
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{
+    Expr, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, LitStr, Pat, PatIdent, Path, ReturnType,
+    Token,
+    TypeTraitObject, meta,
+    parse::{ParseStream, Parser},
+    punctuated::Punctuated,
+};
 
-use crate::utils::{strip_lifetimes, DependentType, Namespace};
+use crate::utils::{self, BindingOptions, DependentType, Namespace, TraitBounds, strip_lifetimes};
 
 pub struct BindingAttributeInputs {
     body_verbatim: TokenStream,
-    is_multi_binding: bool,
     ns: Namespace,
-    trait_: Path,
+    trait_: TraitBounds,
     concrete_impl: DependentType,
+    options: BindingOptions,
+    // The methods of the impl, which the proxy forwards for `#[binding(intercept)]`
+    impl_items: Vec<ImplItem>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BindingKind {
+    /// `#[binding]`
+    Binding,
+    /// `#[multi_binding]`
+    MultiBinding,
+    /// `#[default_binding]`
+    DefaultBinding,
+    /// `#[decorates(dyn Trait)]`
+    Decorator,
+}
+
+impl BindingKind {
+    fn attribute_name(self) -> &'static str {
+        match self {
+            BindingKind::Binding => "binding",
+            BindingKind::MultiBinding => "multi_binding",
+            BindingKind::DefaultBinding => "default_binding",
+            BindingKind::Decorator => "decorates",
+        }
+    }
 }
 
 impl BindingAttributeInputs {
     pub fn from_input(
-        is_multi_binding: bool,
+        kind: BindingKind,
         attr_inputs: proc_macro::TokenStream,
         body_inputs: proc_macro::TokenStream,
     ) -> syn::Result<BindingAttributeInputs> {
-        if !attr_inputs.is_empty() {
-            let error = if is_multi_binding {
-                "#[multi_binding] takes no arguments"
-            } else {
-                "#[binding] takes no arguments"
-            };
-            return Err(syn::Error::new_spanned(
-                TokenStream::from(attr_inputs),
-                error,
-            ));
+        let mut options = BindingOptions {
+            is_multi_binding: kind == BindingKind::MultiBinding,
+            is_default: kind == BindingKind::DefaultBinding,
+            ..BindingOptions::default()
+        };
+        let name = kind.attribute_name();
+        let mut decorated_trait = None;
+        if kind == BindingKind::Decorator {
+            let (trait_, order) = Self::parse_decorator_arguments.parse(attr_inputs)?;
+            decorated_trait = Some(trait_);
+            options.is_decorator = true;
+            options.order = Some(order);
+        } else {
+            Self::parse_arguments(kind, &mut options).parse(attr_inputs)?;
         }
 
         let item = syn::parse::<ItemImpl>(body_inputs.clone())?;
         let Some((_, trait_, _)) = item.trait_ else {
-            let error = if is_multi_binding {
-                "#[multi_binding] must be applied to a trait impl"
-            } else {
-                "#[binding] must be applied to a trait impl"
-            };
-            return Err(syn::Error::new_spanned(item, error));
+            return Err(syn::Error::new_spanned(
+                item,
+                format!("#[{name}] must be applied to a trait impl"),
+            ));
         };
         let concrete_impl = DependentType::from_raw_type(&item.self_ty)?;
+        let mut auto_traits = options.auto_traits.clone();
+        auto_traits.sort_by_key(|path| path.to_token_stream().to_string());
+        let mut trait_ = TraitBounds {
+            trait_,
+            auto_traits,
+        };
+        if let Some(decorated_trait) = decorated_trait {
+            let decorated = DependentType::from_trait_object(&decorated_trait)?;
+            if decorated.trait_.to_token_stream().to_string()
+                != trait_.trait_.to_token_stream().to_string()
+            {
+                return Err(syn::Error::new_spanned(
+                    decorated_trait,
+                    "#[decorates(dyn Trait)] must be applied to an impl of that same trait",
+                ));
+            }
+            trait_ = decorated;
+            if !matches!(concrete_impl, DependentType::RegularType(_)) {
+                return Err(syn::Error::new_spanned(
+                    &item.self_ty,
+                    "Decorators must be implemented for a concrete type",
+                ));
+            }
+        }
+        if options.intercept && !matches!(concrete_impl, DependentType::RegularType(_)) {
+            return Err(syn::Error::new_spanned(
+                &item.self_ty,
+                "#[binding(intercept)] must be applied to an impl for a concrete type",
+            ));
+        }
+        if options.intercept && !trait_.auto_traits.is_empty() {
+            // The interceptors are not Send or Sync, so neither is the proxy
+            return Err(syn::Error::new_spanned(
+                &item.self_ty,
+                "#[binding(intercept)] cannot be combined with bounds",
+            ));
+        }
         let ns = Namespace::from_trait_impl(&trait_, &concrete_impl);
 
         Ok(BindingAttributeInputs {
             body_verbatim: body_inputs.into(),
-            is_multi_binding,
             ns,
             trait_,
             concrete_impl,
+            options,
+            impl_items: item.items,
         })
     }
 
-    pub fn generate_code(self) -> proc_macro::TokenStream {
+    fn parse_arguments(
+        kind: BindingKind,
+        options: &mut BindingOptions,
+    ) -> impl Parser<Output = ()> + '_ {
+        let name = kind.attribute_name();
+        let mut expected = vec!["profile = \"...\"", "bounds = Send + Sync"];
+        match kind {
+            BindingKind::Binding => expected.extend(["key = \"...\"", "intercept"]),
+            BindingKind::MultiBinding => expected.push("order = ..."),
+            BindingKind::DefaultBinding | BindingKind::Decorator => {}
+        }
+        meta::parser(move |meta| {
+            if meta.path.is_ident("profile") {
+                options.profile = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("bounds") {
+                let bounds = meta.value()?;
+                options.auto_traits = Punctuated::<Path, Token![+]>::parse_separated_nonempty(bounds)?
+                    .into_iter()
+                    .collect();
+                Ok(())
+            } else if meta.path.is_ident("key") && kind == BindingKind::Binding {
+                options.key = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("intercept") && kind == BindingKind::Binding {
+                options.intercept = true;
+                Ok(())
+            } else if meta.path.is_ident("order") && kind == BindingKind::MultiBinding {
+                options.order = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(format!(
+                    "Expected #[{name}] or #[{name}({})]",
+                    expected.join(", ")
+                )))
+            }
+        })
+    }
+
+    /// Parses `dyn Trait`, optionally followed by `, order = ...`.
+    fn parse_decorator_arguments(input: ParseStream) -> syn::Result<(TypeTraitObject, Expr)> {
+        let trait_ = input.parse()?;
+        let mut order = syn::parse_quote!(0);
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key != "order" {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "Expected #[decorates(dyn Trait)] or #[decorates(dyn Trait, order = 1)]",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            order = input.parse()?;
+        }
+
+        Ok((trait_, order))
+    }
+
+    /// A binding given by `#[binds(dyn Trait)]` or `#[binds(multi(dyn Trait))]` on a derived type,
+    /// rather than on an impl.
+    pub fn for_derived_type(
+        trait_: TraitBounds,
+        concrete_impl: DependentType,
+        is_multi_binding: bool,
+    ) -> Self {
+        let ns = Namespace::from_trait_impl(&trait_, &concrete_impl);
+
+        BindingAttributeInputs {
+            body_verbatim: TokenStream::new(),
+            ns,
+            trait_,
+            concrete_impl,
+            options: BindingOptions {
+                is_multi_binding,
+                ..BindingOptions::default()
+            },
+            impl_items: Vec::new(),
+        }
+    }
+
+    pub fn generate_code(self) -> syn::Result<proc_macro::TokenStream> {
+        let registration = self.quote_registration()?;
+        let original = self.body_verbatim;
+
+        Ok(quote! {
+            #registration
+            #original
+        }
+        .into())
+    }
+
+    /// Everything needed to register the binding, without the impl it was declared on.
+    pub fn quote_registration(&self) -> syn::Result<TokenStream> {
         let create_fn = self.get_create_fn();
+        let proxy = self.get_proxy()?;
         let binding_meta = self.get_binding_meta();
-        let original = self.body_verbatim;
 
-        quote! {
+        Ok(quote! {
             #create_fn
+            #proxy
             #binding_meta
-            #original
-        }
-        .into()
+        })
     }
 
     fn get_create_fn(&self) -> TokenStream {
         let create_fn_name = self.ns.name_of_create_fn();
         let trait_ = &self.trait_;
 
-        let static_concrete_type = match &self.concrete_impl {
+        if self.options.intercept {
+            let proxy = self.ns.with_suffix("proxy").name_of_provided_type();
+            return quote! {
+                unsafe fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                    let proxy: &#proxy = injector.get_foreign();
+                    let trait_object: &(dyn #trait_) = proxy;
+                    let trait_object = unsafe {
+                        // SAFETY: See safety docs in BindingMeta::create
+                        ::std::mem::transmute::<&(dyn #trait_), &'static (dyn #trait_)>(trait_object)
+                    };
+
+                    ::std::result::Result::Ok(::std::boxed::Box::new(trait_object))
+                }
+            };
+        }
+
+        let static_concrete_type = self.quote_static_concrete_type();
+        let shared = match &self.concrete_impl {
+            DependentType::RegularType(concrete_type) => {
+                let mut concrete_type = concrete_type.clone();
+                strip_lifetimes(&mut concrete_type.path);
+                quote! {{
+                    use ::injector::derive_api::{ShareBorrowed as _, ShareOwned as _};
+                    (&::injector::derive_api::Share::<
+                        <#concrete_type as ::injector::Injectable>::Static,
+                    >::new())
+                        .share(injector, |shared| -> ::std::sync::Arc<dyn #trait_> { shared })
+                }}
+            }
+            _ => quote!(::std::option::Option::None),
+        };
+        quote! {
+            unsafe fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                let concrete_type = injector.get();
+                let static_concrete_type = #static_concrete_type;
+                let trait_object: &'static (dyn #trait_) = &*static_concrete_type;
+
+                ::std::result::Result::Ok(::injector::derive_api::bind(trait_object, #shared))
+            }
+        }
+    }
+
+    /// Quotes an expression that turns `concrete_type`, fetched from the injector, into a
+    /// `'static` reference to it.
+    fn quote_static_concrete_type(&self) -> TokenStream {
+        match &self.concrete_impl {
             DependentType::RegularType(concrete_type) => {
                 let mut concrete_type = concrete_type.clone();
                 strip_lifetimes(&mut concrete_type.path);
@@ -82,41 +289,167 @@ impl BindingAttributeInputs {
             DependentType::TraitObject(concrete_trait) => quote! {
                 unsafe {
                     // SAFETY: See safety docs in BindingMeta::create
-                    ::std::mem::transmute::<&dyn #concrete_trait, &'static dyn #concrete_trait>(concrete_trait)
+                    ::std::mem::transmute::<&(dyn #concrete_trait), &'static (dyn #concrete_trait)>(concrete_trait)
                 }
             },
-            DependentType::CollectionOfTraitObjects(_) => unreachable!(),
-        };
-        quote! {
-            unsafe fn #create_fn_name(injector: &::injector::Injector) -> ::std::boxed::Box<dyn ::std::any::Any> {
+            _ => unreachable!(),
+        }
+    }
+
+    /// For `#[binding(intercept)]`, a component that wraps the implementation and calls every
+    /// `Interceptor` around each of its methods. This is what gets bound to the trait instead.
+    fn get_proxy(&self) -> syn::Result<TokenStream> {
+        if !self.options.intercept {
+            return Ok(quote!());
+        }
+
+        // Bounds are rejected for intercepted bindings, so this is just the trait
+        let trait_ = &self.trait_.trait_;
+        let proxy_ns = self.ns.with_suffix("proxy");
+        let proxy = proxy_ns.name_of_provided_type();
+        let create_fn_name = proxy_ns.name_of_create_fn();
+        let static_concrete_type = self.quote_static_concrete_type();
+        let concrete_type_id = self.concrete_impl.quote_type_id();
+        let create_meta = utils::quote_inject_meta_for_components(
+            &[quote!(#proxy)],
+            &proxy_ns,
+            quote! {
+                ::std::vec![
+                    #concrete_type_id,
+                    ::std::any::TypeId::of::<&'static dyn ::injector::Interceptor>(),
+                ]
+            },
+            quote!(::std::vec![::std::any::TypeId::of::<&'static dyn ::injector::Interceptor>()]),
+        )?;
+        let items = self
+            .impl_items
+            .iter()
+            .map(|item| match item {
+                ImplItem::Fn(method) => Self::quote_intercepted_method(trait_, method),
+                ImplItem::Type(_) | ImplItem::Const(_) => Ok(quote!(#item)),
+                _ => Err(syn::Error::new_spanned(
+                    item,
+                    "#[binding(intercept)] only supports methods, associated types and constants",
+                )),
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        let method_names = self.impl_items.iter().filter_map(|item| match item {
+            ImplItem::Fn(method) => Some(LitStr::new(
+                &method.sig.ident.to_string(),
+                method.sig.ident.span(),
+            )),
+            _ => None,
+        });
+        let mut checked_trait = trait_.clone();
+        strip_lifetimes(&mut checked_trait);
+
+        // The proxy is stored in the injector like a provided type, so it can only hold `'static`
+        // references, which are really borrowed from the injector just like any other component.
+        Ok(quote! {
+            #[allow(non_camel_case_types)]
+            struct #proxy {
+                inner: &'static dyn #trait_,
+                interceptors: ::std::vec::Vec<&'static dyn ::injector::Interceptor>,
+            }
+
+            unsafe fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                // The proxy can't forward methods that the impl leaves to their default
+                const {
+                    ::injector::derive_api::check_intercepted_methods(
+                        <dyn #checked_trait as ::injector::derive_api::Interceptable>::METHODS,
+                        &[#(#method_names),*],
+                    )
+                };
                 let concrete_type = injector.get();
-                let static_concrete_type = #static_concrete_type;
-                let trait_object: &dyn #trait_ = &*static_concrete_type;
+                let inner: &'static dyn #trait_ = #static_concrete_type;
+                let interceptors = unsafe { ::injector::derive_api::interceptors(injector) };
 
-                ::std::boxed::Box::new(trait_object)
+                ::std::result::Result::Ok(::std::boxed::Box::new(#proxy { inner, interceptors }))
             }
-        }
+            #create_meta
+
+            impl #trait_ for #proxy {
+                #(#items)*
+            }
+        })
     }
 
-    fn get_binding_meta(&self) -> TokenStream {
-        let inject_meta_fn = self.ns.name_of_inject_meta_fn();
-        let create_fn_name = self.ns.name_of_create_fn();
-        let trait_ = &self.trait_;
-        let impl_type_id = self.concrete_impl.quote_type_id();
-        let is_multi_binding = self.is_multi_binding;
+    fn quote_intercepted_method(trait_: &Path, method: &ImplItemFn) -> syn::Result<TokenStream> {
+        let mut sig = method.sig.clone();
+        let name = &sig.ident;
+        let method_name = LitStr::new(&name.to_string(), name.span());
 
-        quote! {
-            #[::injector::derive_api::linkme::distributed_slice(::injector::derive_api::BINDING_REGISTRY)]
-            #[linkme(crate = ::injector::derive_api::linkme)]
-            fn #inject_meta_fn() -> ::injector::derive_api::BindingMeta {
-                ::injector::derive_api::BindingMeta {
-                    trait_object: ::std::any::TypeId::of::<&'static dyn #trait_>(),
-                    name: ::std::any::type_name::<dyn #trait_>(),
-                    impl_type: #impl_type_id,
-                    is_multi_binding: #is_multi_binding,
-                    create: #create_fn_name,
+        let mut args = Vec::new();
+        for (position, input) in sig.inputs.iter_mut().enumerate() {
+            match input {
+                FnArg::Receiver(receiver)
+                    if receiver.reference.is_some()
+                        && receiver.mutability.is_none()
+                        && receiver.colon_token.is_none() => {}
+                FnArg::Receiver(receiver) => {
+                    return Err(syn::Error::new_spanned(
+                        receiver,
+                        "Intercepted methods must take &self",
+                    ));
+                }
+                // Patterns like `(a, b): (u32, u32)` can't be forwarded, so name every argument
+                FnArg::Typed(typed) => {
+                    let arg = Ident::new(&format!("arg_{}", position), Span::call_site());
+                    *typed.pat = Pat::Ident(PatIdent {
+                        attrs: Vec::new(),
+                        by_ref: None,
+                        mutability: None,
+                        ident: arg.clone(),
+                        subpat: None,
+                    });
+                    args.push(arg);
                 }
             }
         }
+        if !matches!(sig.inputs.first(), Some(FnArg::Receiver(_))) {
+            return Err(syn::Error::new_spanned(
+                &sig,
+                "Intercepted methods must take &self",
+            ));
+        }
+        let await_ = sig.asyncness.map(|_| quote!(.await));
+        let output = match &sig.output {
+            ReturnType::Default => quote!(()),
+            ReturnType::Type(_, output) => quote!(#output),
+        };
+
+        Ok(quote! {
+            #sig {
+                let call = ::injector::MethodCall {
+                    trait_object: ::std::any::type_name::<dyn #trait_>(),
+                    method: #method_name,
+                };
+                for interceptor in &self.interceptors {
+                    if let ::std::result::Result::Err(rejection) = interceptor.before(&call) {
+                        #[allow(unused_imports)]
+                        use ::injector::derive_api::{RejectByPanicking as _, RejectWithError as _};
+                        let reject = ::injector::derive_api::Reject::<#output>::new();
+                        return (&reject).reject(rejection);
+                    }
+                }
+                let started = ::std::time::Instant::now();
+                let result = <dyn #trait_ as #trait_>::#name(self.inner, #(#args),*)#await_;
+                let elapsed = started.elapsed();
+                for interceptor in self.interceptors.iter().rev() {
+                    interceptor.after(&call, elapsed);
+                }
+                result
+            }
+        })
+    }
+
+    fn get_binding_meta(&self) -> TokenStream {
+        let static_impl_type = if self.options.intercept {
+            self.ns.with_suffix("proxy").name_of_provided_type().into_token_stream()
+        } else {
+            self.concrete_impl.quote_static_type()
+        };
+
+        utils::quote_binding_meta(&self.trait_, &self.ns, static_impl_type, &self.options)
     }
 }
@@ -1,8 +1,17 @@
 use proc_macro2::{Ident, TokenStream};
-use quote::quote;
-use syn::{Attribute, Data, DeriveInput, Fields, GenericParam, Generics, Meta};
+use quote::{ToTokens, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Expr, Fields, GenericParam, Generics, LitStr, Meta, Path, Token,
+    TypePath, TypeTraitObject, parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+};
 
-use crate::utils::{self, DependentType, Namespace};
+use crate::{
+    attributes_for_binding::BindingAttributeInputs,
+    inject_attribute::FieldSource,
+    utils::{self, DependentType, Namespace, TraitBounds},
+};
 
 pub struct InjectableDeriveInputs {
     type_name: Ident,
@@ -10,6 +19,24 @@ pub struct InjectableDeriveInputs {
     has_lifetime: bool,
     // If this is left as None, that means they have their own constructor elsewhere
     fields: Option<Fields>,
+    // #[config(section = "...")] types are deserialised rather than built from their fields
+    config: Option<ConfigSection>,
+    // #[reloadable(watch = ...)] types are only registered inside a `Reloadable` wrapper
+    reloadable_watch: Option<Expr>,
+    // From #[binds(dyn Trait, multi(dyn Other))], for impls that can't be annotated themselves
+    binds: Vec<Binds>,
+    // #[injectable(scope = request)] types are only built inside a `RequestScope`
+    is_request_scoped: bool,
+}
+
+struct Binds {
+    trait_: TraitBounds,
+    is_multi_binding: bool,
+}
+
+struct ConfigSection {
+    section: LitStr,
+    validate: Option<Path>,
 }
 
 impl InjectableDeriveInputs {
@@ -19,13 +46,49 @@ impl InjectableDeriveInputs {
         let type_name = raw_input.ident.clone();
         let ns = Namespace::from_type_name(&type_name);
         let has_lifetime = Self::has_lifetime(&raw_input.generics)?;
-        let fields = Self::get_fields(raw_input)?;
+        let config = Self::get_config_section(&raw_input.attrs)?;
+        if let (Some(config), true) = (&config, has_lifetime) {
+            return Err(syn::Error::new_spanned(
+                &config.section,
+                "#[config] types are deserialised, so they cannot borrow from other components",
+            ));
+        }
+        let reloadable_watch = Self::get_reloadable_watch(&raw_input.attrs)?;
+        let binds = Self::get_binds(&raw_input.attrs)?;
+        if let (Some(watch), [_, ..]) = (&reloadable_watch, binds.as_slice()) {
+            return Err(syn::Error::new_spanned(
+                watch,
+                "#[reloadable] types can only be used through their Reloadable wrapper, so they cannot be bound with #[binds]",
+            ));
+        }
+        let is_request_scoped = Self::is_request_scoped(&raw_input.attrs)?;
+        if let (Some(watch), true) = (&reloadable_watch, is_request_scoped) {
+            return Err(syn::Error::new_spanned(
+                watch,
+                "#[reloadable] types are shared by every request, so they cannot be request scoped",
+            ));
+        }
+        if let ([binds, ..], true) = (binds.as_slice(), is_request_scoped) {
+            return Err(syn::Error::new_spanned(
+                &binds.trait_,
+                "Request scoped types cannot be bound with #[binds]",
+            ));
+        }
+        let fields = if config.is_some() {
+            None
+        } else {
+            Self::get_fields(raw_input)?
+        };
 
         Ok(InjectableDeriveInputs {
             type_name,
             ns,
             has_lifetime,
             fields,
+            config,
+            reloadable_watch,
+            binds,
+            is_request_scoped,
         })
     }
 
@@ -94,17 +157,160 @@ impl InjectableDeriveInputs {
         }
     }
 
+    fn get_config_section(attrs: &[Attribute]) -> syn::Result<Option<ConfigSection>> {
+        let config_attrs = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("config"))
+            .collect::<Vec<_>>();
+        let attr = match config_attrs.as_slice() {
+            [] => return Ok(None),
+            [single] => single,
+            [_, second, ..] => {
+                return Err(syn::Error::new_spanned(
+                    second,
+                    "Only one #[config] attribute is allowed",
+                ));
+            }
+        };
+        if let Some(other) = attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("has_constructor"))
+        {
+            return Err(syn::Error::new_spanned(
+                other,
+                "#[config] types cannot also have a constructor",
+            ));
+        }
+
+        let mut section = None;
+        let mut validate = None;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("section") {
+                section = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("validate") {
+                validate = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "Expected #[config(section = \"...\")] or #[config(section = \"...\", validate = some_fn)]",
+                ))
+            }
+        })?;
+
+        let Some(section) = section else {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[config] needs the section to read from, like #[config(section = \"database\")]",
+            ));
+        };
+        Ok(Some(ConfigSection { section, validate }))
+    }
+
+    fn get_reloadable_watch(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+        let reloadable_attrs = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("reloadable"))
+            .collect::<Vec<_>>();
+        let attr = match reloadable_attrs.as_slice() {
+            [] => return Ok(None),
+            [single] => single,
+            [_, second, ..] => {
+                return Err(syn::Error::new_spanned(
+                    second,
+                    "Only one #[reloadable] attribute is allowed",
+                ));
+            }
+        };
+        if let Some(other) = attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("has_constructor"))
+        {
+            return Err(syn::Error::new_spanned(
+                other,
+                "#[reloadable] types cannot also have a constructor",
+            ));
+        }
+
+        let mut watch = None;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("watch") {
+                watch = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("Expected #[reloadable(watch = \"path/to/file\")]"))
+            }
+        })?;
+
+        let Some(watch) = watch else {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[reloadable] needs the file to watch, like #[reloadable(watch = \"limits.toml\")]",
+            ));
+        };
+        Ok(Some(watch))
+    }
+
+    fn get_binds(attrs: &[Attribute]) -> syn::Result<Vec<Binds>> {
+        let mut binds = Vec::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("binds")) {
+            binds.extend(attr.parse_args_with(Punctuated::<Binds, Token![,]>::parse_terminated)?);
+        }
+
+        Ok(binds)
+    }
+
+    fn is_request_scoped(attrs: &[Attribute]) -> syn::Result<bool> {
+        let mut is_request_scoped = false;
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("injectable")) {
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("scope") {
+                    return Err(meta.error("Expected #[injectable(scope = request)]"));
+                }
+                let scope: Ident = meta.value()?.parse()?;
+                if scope == "request" {
+                    is_request_scoped = true;
+                } else if scope == "singleton" {
+                    is_request_scoped = false;
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        scope,
+                        "Expected the scope to be `request` or `singleton`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(is_request_scoped)
+    }
+
     pub fn derive(self) -> syn::Result<proc_macro::TokenStream> {
         let base_impl = self.get_base_impl();
         let static_impl = self.get_static_impl();
         let create_fn = self.get_create_fn()?;
+        let reloadable_create_fn = self.get_reloadable_create_fn();
         let create_meta = self.get_create_meta()?;
+        let bindings = self.get_bindings()?;
+        let scope = self.get_scope_registration();
+
+        let mut constructors = quote! {
+            #create_fn
+            #reloadable_create_fn
+            #create_meta
+        };
+        if self.config.is_some() {
+            constructors = quote! {
+                ::injector::derive_api::requires_config_feature! { #constructors }
+            };
+        }
 
         Ok(quote! {
             #base_impl
             #static_impl
-            #create_fn
-            #create_meta
+            #constructors
+            #bindings
+            #scope
         }
         .into())
     }
@@ -117,6 +323,7 @@ impl InjectableDeriveInputs {
             impl <'a> ::injector::Injectable<'a> for #borrowed_type {
                 type Static = #static_type;
 
+                #[allow(clippy::useless_transmute)]
                 unsafe fn upcast(self) -> Self::Static {
                     // SAFETY: see docs for upcast in the trait declaration. This is exactly what we
                     // are meant to do here.
@@ -142,28 +349,150 @@ impl InjectableDeriveInputs {
     }
 
     fn get_create_meta(&self) -> syn::Result<TokenStream> {
-        let Some(fields) = &self.fields else {
+        let (deps, collections) = if self.config.is_some() {
+            (
+                quote!(::std::vec![::std::any::TypeId::of::<::injector::config::Config>()]),
+                quote!(::std::vec![]),
+            )
+        } else if let Some(fields) = &self.fields {
+            let sources = fields
+                .iter()
+                .map(FieldSource::from_field)
+                .collect::<syn::Result<Vec<_>>>()?;
+            let deps = sources.iter().map(FieldSource::quote_dependencies);
+            let collections = sources.iter().map(FieldSource::quote_collections);
+            (
+                quote!(::std::iter::empty()#(.chain(#deps))*.collect()),
+                quote!(::std::iter::empty()#(.chain(#collections))*.collect()),
+            )
+        } else {
             // If there's no fields, they will need to get their create_meta from the constructor
             return Ok(quote! {});
         };
 
-        let deps = fields.iter().map(DependentType::from_field);
-        utils::quote_inject_meta(&self.type_name, &self.ns, deps)
+        if self.reloadable_watch.is_some() {
+            let static_type = self.static_self_type();
+            return utils::quote_inject_meta_for_components(
+                &[quote!(::injector::Reloadable<'static, #static_type>)],
+                &self.reloadable_ns(),
+                deps,
+                collections,
+            );
+        }
+
+        utils::quote_inject_meta_for_components(
+            &[self.type_name.to_token_stream()],
+            &self.ns,
+            deps,
+            collections,
+        )
     }
 
-    fn get_create_fn(&self) -> syn::Result<TokenStream> {
-        let Some(fields) = &self.fields else {
-            return Ok(quote!());
+    fn get_bindings(&self) -> syn::Result<TokenStream> {
+        let type_name = &self.type_name;
+
+        self.binds
+            .iter()
+            .map(|binds| {
+                BindingAttributeInputs::for_derived_type(
+                    binds.trait_.clone(),
+                    DependentType::RegularType(TypePath {
+                        qself: None,
+                        path: type_name.clone().into(),
+                    }),
+                    binds.is_multi_binding,
+                )
+                .quote_registration()
+            })
+            .collect()
+    }
+
+    /// Request scoped types keep their usual registration, and are also listed as request scoped
+    /// so that the `Injector` leaves them to each `RequestScope`. This way their constructors can
+    /// be registered by `#[constructor]` just like any other.
+    fn get_scope_registration(&self) -> TokenStream {
+        if !self.is_request_scoped {
+            return quote!();
+        }
+        let static_type = self.static_self_type();
+        let scope_fn_name = self.ns.with_suffix("request_scoped").name_of_inject_meta_fn();
+
+        quote! {
+            #[::injector::derive_api::linkme::distributed_slice(::injector::derive_api::REQUEST_SCOPED_REGISTRY)]
+            #[linkme(crate = ::injector::derive_api::linkme)]
+            fn #scope_fn_name() -> ::injector::derive_api::ScopeMeta {
+                ::injector::derive_api::ScopeMeta {
+                    this: ::std::any::TypeId::of::<#static_type>(),
+                    name: ::std::any::type_name::<#static_type>(),
+                    is_send: {
+                        use ::injector::derive_api::{IsNotSend as _, IsSend as _};
+                        (&::injector::derive_api::SendCheck::<#static_type>::new()).is_send()
+                    },
+                }
+            }
+        }
+    }
+
+    fn reloadable_ns(&self) -> Namespace {
+        self.ns.with_suffix("reloadable")
+    }
+
+    /// Reloadable types still get a regular create function, but it is only called through the
+    /// `Reloadable` wrapper, which gets a create function of its own.
+    fn get_reloadable_create_fn(&self) -> TokenStream {
+        let Some(watch) = &self.reloadable_watch else {
+            return quote!();
         };
+        let static_type = self.static_self_type();
+        let inner_create_fn_name = self.ns.name_of_create_fn();
+        let create_fn_name = self.reloadable_ns().name_of_create_fn();
 
+        quote! {
+            fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                ::injector::derive_api::create_reloadable::<#static_type>(injector, #watch, #inner_create_fn_name)
+            }
+        }
+    }
+
+    fn get_create_fn(&self) -> syn::Result<TokenStream> {
         let type_name = &self.type_name;
+        let constructed = match (&self.fields, &self.config) {
+            (_, Some(ConfigSection { section, validate })) => {
+                let validate = validate.iter();
+                let from_config = if self.reloadable_watch.is_some() {
+                    quote!(from_reloaded_config)
+                } else {
+                    quote!(from_config)
+                };
+                quote! {{
+                    let config = ::injector::derive_api::#from_config(injector, #section)?;
+                    #(::injector::derive_api::validate_config(#section, &config, #validate)?;)*
+                    config
+                }}
+            }
+            (Some(fields), None) => Self::construct_from_fields(type_name, fields)?,
+            (None, None) => return Ok(quote!()),
+        };
+
+        let create_fn_name = self.ns.name_of_create_fn();
+        Ok(quote! {
+            fn #create_fn_name(injector: &::injector::Injector) -> ::std::result::Result<::std::boxed::Box<dyn ::std::any::Any>, ::injector::ComponentError> {
+                let constructed: #type_name = #constructed;
+                ::std::result::Result::Ok(::std::boxed::Box::new(unsafe {
+                    <#type_name as ::injector::Injectable>::upcast(constructed)
+                }))
+            }
+        })
+    }
+
+    fn construct_from_fields(type_name: &Ident, fields: &Fields) -> syn::Result<TokenStream> {
         let constructed = match fields {
             Fields::Named(fields) => {
                 let fields = fields
                     .named
                     .iter()
                     .map(|field| {
-                        let dependency = DependentType::from_field(&field)?.quote_get_call();
+                        let dependency = FieldSource::from_field(field)?.quote_value();
                         let field_name = field.ident.as_ref().unwrap();
                         Ok(quote! { #field_name: #dependency })
                     })
@@ -174,22 +503,14 @@ impl InjectableDeriveInputs {
                 let fields = fields
                     .unnamed
                     .iter()
-                    .map(|field| DependentType::from_field(&field).map(|dep| dep.quote_get_call()))
+                    .map(|field| FieldSource::from_field(field).map(|source| source.quote_value()))
                     .collect::<syn::Result<Vec<_>>>()?;
                 quote! { #type_name(#(#fields),*) }
             }
             Fields::Unit => quote! { #type_name },
         };
 
-        let create_fn_name = self.ns.name_of_create_fn();
-        Ok(quote! {
-            fn #create_fn_name(injector: &::injector::Injector) -> ::std::boxed::Box<dyn ::std::any::Any> {
-                let constructed = #constructed;
-                ::std::boxed::Box::new(unsafe {
-                    <#type_name as ::injector::Injectable>::upcast(constructed)
-                })
-            }
-        })
+        Ok(constructed)
     }
 
     fn static_self_type(&self) -> TokenStream {
@@ -210,3 +531,28 @@ impl InjectableDeriveInputs {
         }
     }
 }
+
+impl Parse for Binds {
+    /// Parses `dyn Trait` or `multi(dyn Trait)`.
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let is_multi_binding = input.peek(syn::Ident) && input.fork().parse::<Ident>()? == "multi";
+        let trait_object = if is_multi_binding {
+            input.parse::<Ident>()?;
+            let content;
+            parenthesized!(content in input);
+            content.parse::<TypeTraitObject>()?
+        } else {
+            input.parse::<TypeTraitObject>().map_err(|error| {
+                syn::Error::new(
+                    error.span(),
+                    "Expected #[binds(dyn Trait)] or #[binds(multi(dyn Trait))]",
+                )
+            })?
+        };
+
+        Ok(Binds {
+            trait_: DependentType::from_trait_object(&trait_object)?,
+            is_multi_binding,
+        })
+    }
+}
@@ -0,0 +1,372 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    Attribute, Expr, ExprLit, ExprUnary, Field, Lit, LitStr, Member, Path, Token, Type, UnOp,
+    punctuated::Punctuated, spanned::Spanned,
+};
+
+use crate::utils::DependentType;
+
+/// Where the value for a field of an `#[derive(Injectable)]` struct comes from. By default, fields
+/// are references to other components, but an `#[inject(...)]` attribute can change that.
+pub enum FieldSource {
+    /// A reference to another component, or a collection of multi-bound trait objects.
+    Dependency(DependentType),
+    /// `#[inject(default)]`: initialised with `Default::default()`.
+    Default,
+    /// `#[inject(with = some_fn)]`: initialised by calling `some_fn`, with its arguments supplied
+    /// by the injector.
+    With(Path),
+    /// `#[inject(from = Config, path = db)]`: a reference to a field inside another component.
+    Projection {
+        from: DependentType,
+        path: Vec<Member>,
+    },
+    /// `#[inject(clone)]`: an owned clone of what the inner source would have been a reference to.
+    Cloned(Box<FieldSource>),
+    /// `#[inject(env = "PORT", default = 8080)]`: parsed from an environment variable.
+    Env {
+        variable: LitStr,
+        default: Option<String>,
+        ty: Type,
+    },
+}
+
+mod error_messages {
+    pub const UNKNOWN_ARGUMENT: &str = "Expected one of #[inject(default)], \
+        #[inject(with = some_fn)], #[inject(clone)], #[inject(from = Component, path = field)] or \
+        #[inject(env = \"VARIABLE\")]";
+}
+
+/// The arguments given to an `#[inject(...)]` attribute, before we work out which combinations of
+/// them make sense.
+#[derive(Default)]
+struct InjectArguments {
+    // Every argument we were given, in order, so we can point at the ones that don't belong.
+    given: Vec<Path>,
+    default: Option<Option<Expr>>,
+    with: Option<Path>,
+    from: Option<Type>,
+    path: Option<Punctuated<Member, Token![.]>>,
+    clone: bool,
+    env: Option<LitStr>,
+}
+
+impl InjectArguments {
+    fn parse(attr: &Attribute) -> syn::Result<Self> {
+        let mut args = InjectArguments::default();
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                args.default = Some(if meta.input.peek(Token![=]) {
+                    Some(meta.value()?.parse()?)
+                } else {
+                    None
+                });
+            } else if meta.path.is_ident("with") {
+                args.with = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("from") {
+                args.from = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("path") {
+                let value = meta.value()?;
+                args.path = Some(Punctuated::parse_separated_nonempty(value)?);
+            } else if meta.path.is_ident("clone") {
+                args.clone = true;
+            } else if meta.path.is_ident("env") {
+                args.env = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(error_messages::UNKNOWN_ARGUMENT));
+            }
+            args.given.push(meta.path);
+            Ok(())
+        })?;
+
+        Ok(args)
+    }
+
+    /// Errors on the first argument we were given that isn't one of `allowed`.
+    fn allow_only(&self, allowed: &[&str]) -> syn::Result<()> {
+        let Some(unexpected) = self
+            .given
+            .iter()
+            .find(|given| !allowed.iter().any(|allowed| given.is_ident(allowed)))
+        else {
+            return Ok(());
+        };
+
+        let allowed = allowed
+            .iter()
+            .map(|allowed| format!("`{}`", allowed))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(syn::Error::new_spanned(
+            unexpected,
+            format!("This can only be combined with {} here", allowed),
+        ))
+    }
+}
+
+impl FieldSource {
+    pub fn from_field(field: &Field) -> syn::Result<Self> {
+        let Some(attr) = Self::get_inject_attribute(&field.attrs)? else {
+            return DependentType::from_field(field).map(FieldSource::Dependency);
+        };
+        let args = InjectArguments::parse(attr)?;
+
+        if let Some(variable) = args.env.clone() {
+            args.allow_only(&["env", "default"])?;
+            return Self::env(field, variable, args.default.flatten());
+        }
+
+        if let Some(Some(value)) = &args.default {
+            return Err(syn::Error::new_spanned(
+                value,
+                "Default values can only be given for #[inject(env = \"...\")] fields",
+            ));
+        }
+
+        if args.default.is_some() {
+            args.allow_only(&["default"])?;
+            return Ok(FieldSource::Default);
+        }
+
+        if let Some(function) = args.with.clone() {
+            args.allow_only(&["with"])?;
+            return Ok(FieldSource::With(function));
+        }
+
+        if args.from.is_some() || args.path.is_some() {
+            args.allow_only(&["from", "path", "clone"])?;
+            let (from, path) = match (args.from, args.path) {
+                (Some(from), Some(path)) => (from, path),
+                (Some(from), None) => {
+                    return Err(syn::Error::new_spanned(
+                        from,
+                        "#[inject(from = ...)] also needs a `path = ...` to the field to inject",
+                    ));
+                }
+                (None, Some(path)) => {
+                    return Err(syn::Error::new_spanned(
+                        path,
+                        "#[inject(path = ...)] also needs a `from = ...` component to project from",
+                    ));
+                }
+                (None, None) => unreachable!(),
+            };
+            if !args.clone && !matches!(field.ty, Type::Reference(_)) {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "Fields projected from another component must be references, unless they \
+                    are marked with #[inject(clone)]",
+                ));
+            }
+            let projection = Self::projection(from, path)?;
+            return Ok(Self::maybe_cloned(projection, args.clone));
+        }
+
+        if args.clone {
+            args.allow_only(&["clone"])?;
+            if DependentType::arc_contents(&field.ty).is_some() {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "Arc fields are already shared with the injector, so leave out #[inject(clone)]",
+                ));
+            }
+            return match DependentType::from_raw_type(&field.ty)? {
+                DependentType::RegularType(ty) => Ok(FieldSource::Cloned(Box::new(
+                    FieldSource::Dependency(DependentType::RegularType(ty)),
+                ))),
+                _ => Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "Only plain types can be injected with #[inject(clone)]",
+                )),
+            };
+        }
+
+        Err(syn::Error::new_spanned(
+            attr,
+            error_messages::UNKNOWN_ARGUMENT,
+        ))
+    }
+
+    fn env(field: &Field, variable: LitStr, default: Option<Expr>) -> syn::Result<Self> {
+        // Defaults are treated as if the environment variable had been set to them, so that they
+        // go through the same parsing as the real value.
+        let default = match default {
+            None => None,
+            Some(Expr::Lit(ExprLit { lit, .. })) => Some(Self::env_default(&lit)?),
+            Some(Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            })) => match *expr {
+                Expr::Lit(ExprLit {
+                    lit: lit @ (Lit::Int(_) | Lit::Float(_)),
+                    ..
+                }) => Some(format!("-{}", Self::env_default(&lit)?)),
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "Only numbers can be negated in the default for an environment variable",
+                    ));
+                }
+            },
+            Some(other) => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "Default values for environment variables must be literals",
+                ));
+            }
+        };
+
+        Ok(FieldSource::Env {
+            variable,
+            default,
+            ty: field.ty.clone(),
+        })
+    }
+
+    /// The default as the environment variable would spell it, so without any suffix, escapes or
+    /// quotes.
+    fn env_default(lit: &Lit) -> syn::Result<String> {
+        match lit {
+            Lit::Str(value) => Ok(value.value()),
+            Lit::Char(value) => Ok(value.value().to_string()),
+            Lit::Int(value) => Ok(value.base10_digits().to_string()),
+            Lit::Float(value) => Ok(value.base10_digits().to_string()),
+            Lit::Bool(value) => Ok(value.value.to_string()),
+            other => Err(syn::Error::new_spanned(
+                other,
+                "Default values for environment variables must be strings, chars, numbers or bools",
+            )),
+        }
+    }
+
+    fn projection(from: Type, path: Punctuated<Member, Token![.]>) -> syn::Result<Self> {
+        match DependentType::from_raw_type(&from)? {
+            DependentType::RegularType(from) => Ok(FieldSource::Projection {
+                from: DependentType::RegularType(from),
+                path: path.into_iter().collect(),
+            }),
+            _ => Err(syn::Error::new_spanned(
+                from,
+                "Fields can only be projected from plain types",
+            )),
+        }
+    }
+
+    fn maybe_cloned(source: Self, clone: bool) -> Self {
+        if clone {
+            FieldSource::Cloned(Box::new(source))
+        } else {
+            source
+        }
+    }
+
+    fn get_inject_attribute(attrs: &[Attribute]) -> syn::Result<Option<&Attribute>> {
+        let inject_attrs = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("inject"))
+            .collect::<Vec<_>>();
+
+        match inject_attrs.as_slice() {
+            [] => Ok(None),
+            [single] => {
+                if let Some(other) = attrs
+                    .iter()
+                    .find(|attr| {
+                        attr.path().is_ident("from_multi_binding")
+                            || attr.path().is_ident("from_chain")
+                    })
+                {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "#[from_multi_binding] and #[from_chain] cannot be combined with #[inject]",
+                    ));
+                }
+                Ok(Some(single))
+            }
+            [_, second, ..] => Err(syn::Error::new_spanned(
+                second,
+                "Only one #[inject] attribute is allowed",
+            )),
+        }
+    }
+
+    /// Quotes an expression for the value of this field, within a create function.
+    pub fn quote_value(&self) -> TokenStream {
+        match self {
+            FieldSource::Dependency(dependency) => dependency.quote_get_call(),
+            FieldSource::Default => quote!(::std::default::Default::default()),
+            FieldSource::With(function) => {
+                // Spanned so that an argument which can't be injected points at the function
+                let arguments = (0..6).map(syn::Index::from).map(|argument| {
+                    quote_spanned!(function.span() => (&arguments.#argument).resolve(injector))
+                });
+                quote!({
+                    use ::injector::derive_api::{
+                        ResolveForeign as _, ResolveInjectable as _, ResolveTraitObject as _,
+                    };
+                    let arguments = ::injector::derive_api::InjectWith::arguments(&#function);
+                    ::injector::derive_api::InjectWith::call(&#function, (#(#arguments,)*))
+                })
+            }
+            FieldSource::Projection { from, path } => {
+                let from = from.quote_get_call();
+                quote!(&#from #(.#path)*)
+            }
+            FieldSource::Cloned(inner) => {
+                let inner = inner.quote_value();
+                quote!(::std::clone::Clone::clone(#inner))
+            }
+            FieldSource::Env {
+                variable,
+                default,
+                ty,
+            } => {
+                let default = match default {
+                    Some(default) => quote!(::std::option::Option::Some(#default)),
+                    None => quote!(::std::option::Option::None),
+                };
+                quote!(::injector::derive_api::from_env::<#ty>(#variable, #default)?)
+            }
+        }
+    }
+
+    /// Like [`Self::quote_dependencies`], but only for the dependencies that are collections of
+    /// multi bindings.
+    pub fn quote_collections(&self) -> TokenStream {
+        match self {
+            FieldSource::Dependency(dependency) if dependency.is_collection() => {
+                let type_id = dependency.quote_type_id();
+                quote!([#type_id])
+            }
+            FieldSource::Cloned(inner) => inner.quote_collections(),
+            _ => quote!([]),
+        }
+    }
+
+    /// Quotes an expression for the type IDs this field depends on, as something iterable.
+    pub fn quote_dependencies(&self) -> TokenStream {
+        match self {
+            FieldSource::Dependency(dependency)
+            | FieldSource::Projection {
+                from: dependency, ..
+            } => {
+                let type_id = dependency.quote_type_id();
+                quote!([#type_id])
+            }
+            FieldSource::Default | FieldSource::Env { .. } => quote!([]),
+            FieldSource::With(function) => {
+                let arguments = (0..6).map(syn::Index::from);
+                quote!({
+                    use ::injector::derive_api::{
+                        ResolveForeign as _, ResolveInjectable as _, ResolveTraitObject as _,
+                    };
+                    let arguments = ::injector::derive_api::InjectWith::arguments(&#function);
+                    [#((&arguments.#arguments).key(),)*].into_iter().flatten()
+                })
+            }
+            FieldSource::Cloned(inner) => inner.quote_dependencies(),
+        }
+    }
+}
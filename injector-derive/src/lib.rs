@@ -1,10 +1,27 @@
 mod attribute_constructor;
+mod attribute_interceptable;
+mod attribute_provides;
 mod attributes_for_binding;
 mod derive_injectable;
 
+mod inject_attribute;
 mod utils;
 
-#[proc_macro_derive(Injectable, attributes(has_constructor, from_multi_binding))]
+use attributes_for_binding::{BindingAttributeInputs, BindingKind};
+
+#[proc_macro_derive(
+    Injectable,
+    attributes(
+        has_constructor,
+        from_multi_binding,
+        from_chain,
+        inject,
+        config,
+        reloadable,
+        binds,
+        injectable
+    )
+)]
 pub fn derive_injectable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = match derive_injectable::InjectableDeriveInputs::from_input(input) {
         Ok(input) => input,
@@ -32,17 +49,42 @@ pub fn constructor(
 }
 
 #[proc_macro_attribute]
-pub fn binding(
+pub fn provides(
     attr: proc_macro::TokenStream,
     body: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let input = match attributes_for_binding::BindingAttributeInputs::from_input(false, attr, body)
+    let input = match attribute_provides::ProvidesAttributeInputs::from_input(attr, body) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    input
+        .generate_code()
+        .unwrap_or_else(|err| err.to_compile_error().into())
+}
+
+#[proc_macro_attribute]
+pub fn interceptable(
+    attr: proc_macro::TokenStream,
+    body: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = match attribute_interceptable::InterceptableAttributeInputs::from_input(attr, body)
     {
         Ok(input) => input,
         Err(err) => return err.to_compile_error().into(),
     };
 
-    input.generate_code()
+    input
+        .generate_code()
+        .unwrap_or_else(|err| err.to_compile_error().into())
+}
+
+#[proc_macro_attribute]
+pub fn binding(
+    attr: proc_macro::TokenStream,
+    body: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    generate_binding(BindingKind::Binding, attr, body)
 }
 
 #[proc_macro_attribute]
@@ -50,10 +92,36 @@ pub fn multi_binding(
     attr: proc_macro::TokenStream,
     body: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let input = match attributes_for_binding::BindingAttributeInputs::from_input(true, attr, body) {
+    generate_binding(BindingKind::MultiBinding, attr, body)
+}
+
+#[proc_macro_attribute]
+pub fn default_binding(
+    attr: proc_macro::TokenStream,
+    body: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    generate_binding(BindingKind::DefaultBinding, attr, body)
+}
+
+#[proc_macro_attribute]
+pub fn decorates(
+    attr: proc_macro::TokenStream,
+    body: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    generate_binding(BindingKind::Decorator, attr, body)
+}
+
+fn generate_binding(
+    kind: BindingKind,
+    attr: proc_macro::TokenStream,
+    body: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = match BindingAttributeInputs::from_input(kind, attr, body) {
         Ok(input) => input,
         Err(err) => return err.to_compile_error().into(),
     };
 
-    input.generate_code()
+    input
+        .generate_code()
+        .unwrap_or_else(|err| err.to_compile_error().into())
 }
@@ -4,22 +4,56 @@ use convert_case::{Case, Casing};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{ToTokens, quote};
 use syn::{
-    Attribute, Field, FnArg, GenericArgument, Path, PathArguments, TraitBoundModifier, Type,
-    TypeParamBound, TypePath, TypeTraitObject, spanned::Spanned,
+    Attribute, Expr, Field, FnArg, GenericArgument, LitStr, Path, PathArguments,
+    TraitBoundModifier, Type, TypeParamBound, TypePath, TypeTraitObject, spanned::Spanned,
 };
 
 mod error_messages {
-    pub const NEEDS_BORROW: &str = "Types supplied by the injector must be references";
+    pub const NEEDS_BORROW: &str = "Types supplied by the injector must be references or Arcs";
     pub const SIMPLE_DEPS_ONLY: &str = "Only simple types can be injected at this time";
     pub const NO_RECEIVER: &str = "Constructor functions cannot take receiver parameters";
     pub const SIMPLE_TRAIT_BOUNDS_ONLY: &str =
         "Only simple trait bounds can be injected at this time";
 }
 
+/// The traits that can follow the main trait in a trait object, like `dyn Service + Send + Sync`.
+const AUTO_TRAITS: &[&str] = &["Send", "Sync", "Unpin", "UnwindSafe", "RefUnwindSafe"];
+
 pub enum DependentType {
     RegularType(TypePath),
-    TraitObject(Path),
-    CollectionOfTraitObjects(Path),
+    TraitObject(TraitBounds),
+    CollectionOfTraitObjects(TraitBounds),
+    ChainOfTraitObjects(TraitBounds),
+    // `Arc<T>` and `Arc<dyn Trait>`, which are only available for `Owned` types
+    SharedType(TypePath),
+    SharedTraitObject(TraitBounds),
+}
+
+/// The bounds of a trait object, like `Service + Send + Sync`. This quotes as just the bounds, so
+/// wrap it as `(dyn #bounds)` when taking a reference to it.
+#[derive(Clone)]
+pub struct TraitBounds {
+    pub trait_: Path,
+    // Sorted, so that the namespace doesn't depend on the order they were written in
+    pub auto_traits: Vec<Path>,
+}
+
+/// Everything about a binding that is given in its attribute, like `#[binding(key = "...")]`.
+#[derive(Default)]
+pub struct BindingOptions {
+    pub is_multi_binding: bool,
+    pub is_default: bool,
+    // Only applies when that profile is active
+    pub profile: Option<LitStr>,
+    // Only applies when selected with that key
+    pub key: Option<LitStr>,
+    pub is_decorator: bool,
+    // Given with `#[multi_binding(order = ...)]` or `#[decorates(dyn Trait, order = ...)]`
+    pub order: Option<Expr>,
+    // Set for `#[binding(intercept)]`, which binds a proxy that calls the interceptors instead
+    pub intercept: bool,
+    // Given with `#[binding(bounds = Send + Sync)]`, to bind `dyn Trait + Send + Sync` instead
+    pub auto_traits: Vec<Path>,
 }
 
 pub struct Namespace {
@@ -57,6 +91,8 @@ impl DependentType {
             Type::TraitObject(trait_) => {
                 Ok(DependentType::TraitObject(Self::from_trait_object(trait_)?))
             }
+            // Like `&'a (dyn Service + Send + Sync)`
+            Type::Paren(inner) => Self::from_raw_type(&inner.elem),
             other => Err(syn::Error::new_spanned(
                 other,
                 error_messages::SIMPLE_DEPS_ONLY,
@@ -66,26 +102,54 @@ impl DependentType {
 
     pub fn quote_get_call(&self) -> TokenStream {
         match self {
-            DependentType::RegularType(_) => quote!(injector.get()),
+            DependentType::RegularType(ty) => {
+                let mut ty = ty.clone();
+                strip_lifetimes(&mut ty.path);
+                quote! {{
+                    use ::injector::derive_api::{ResolveForeign as _, ResolveInjectable as _};
+                    (&::injector::derive_api::Resolve::<#ty>::new()).resolve(injector)
+                }}
+            }
             DependentType::TraitObject(_) => quote!(injector.get_trait_object()),
             DependentType::CollectionOfTraitObjects(_) => quote!(
                 ::std::iter::FromIterator::from_iter(injector.get_all_trait_objects())
             ),
+            DependentType::ChainOfTraitObjects(_) => quote!(::injector::derive_api::chain(injector)),
+            DependentType::SharedType(ty) => quote!(injector.get_arc::<#ty>()),
+            DependentType::SharedTraitObject(bounds) => {
+                quote!(injector.get_trait_object_arc::<dyn #bounds>())
+            }
         }
     }
 
+    /// Whether this is every multi binding of a trait, which is fine to be empty.
+    pub fn is_collection(&self) -> bool {
+        matches!(
+            self,
+            DependentType::CollectionOfTraitObjects(_) | DependentType::ChainOfTraitObjects(_)
+        )
+    }
+
     pub fn quote_type_id(&self) -> impl ToTokens {
+        let static_type = self.quote_static_type();
+        quote!(::std::any::TypeId::of::<#static_type>())
+    }
+
+    /// The `'static` type that this is stored as in the injector.
+    pub fn quote_static_type(&self) -> TokenStream {
         match self {
-            DependentType::RegularType(ty) => {
+            DependentType::RegularType(ty) | DependentType::SharedType(ty) => {
                 let mut ty = ty.clone();
                 strip_lifetimes(&mut ty.path);
-                quote!(::std::any::TypeId::of::<#ty>())
+                quote!(#ty)
             }
-            DependentType::TraitObject(trait_)
-            | DependentType::CollectionOfTraitObjects(trait_) => {
-                let mut trait_ = trait_.clone();
-                strip_lifetimes(&mut trait_);
-                quote!(::std::any::TypeId::of::<&'static dyn #trait_>())
+            DependentType::TraitObject(bounds)
+            | DependentType::CollectionOfTraitObjects(bounds)
+            | DependentType::ChainOfTraitObjects(bounds)
+            | DependentType::SharedTraitObject(bounds) => {
+                let mut bounds = bounds.clone();
+                strip_lifetimes(&mut bounds.trait_);
+                quote!(&'static (dyn #bounds))
             }
         }
     }
@@ -93,16 +157,44 @@ impl DependentType {
     fn from_reference_type(ty: &Type) -> syn::Result<Self> {
         match ty {
             Type::Reference(referenced_type) => Self::from_raw_type(&referenced_type.elem),
-            other => Err(syn::Error::new_spanned(other, error_messages::NEEDS_BORROW)),
+            other => match Self::arc_contents(other) {
+                Some(shared) => match Self::from_raw_type(shared)? {
+                    DependentType::RegularType(ty) => Ok(DependentType::SharedType(ty)),
+                    DependentType::TraitObject(bounds) => {
+                        Ok(DependentType::SharedTraitObject(bounds))
+                    }
+                    _ => Err(syn::Error::new_spanned(other, error_messages::SIMPLE_DEPS_ONLY)),
+                },
+                None => Err(syn::Error::new_spanned(other, error_messages::NEEDS_BORROW)),
+            },
+        }
+    }
+
+    /// The `T` in `Arc<T>` or `std::sync::Arc<T>`.
+    pub fn arc_contents(ty: &Type) -> Option<&Type> {
+        let Type::Path(path) = ty else {
+            return None;
+        };
+        let last = path.path.segments.last()?;
+        if last.ident != "Arc" {
+            return None;
+        }
+        let PathArguments::AngleBracketed(generics) = &last.arguments else {
+            return None;
+        };
+        match generics.args.first() {
+            Some(GenericArgument::Type(inner)) if generics.args.len() == 1 => Some(inner),
+            _ => None,
         }
     }
 
     fn from_attributes(attrs: &[Attribute]) -> syn::Result<Option<Self>> {
         let attrs = attrs
             .iter()
-            .filter(|attr| attr.path().is_ident("from_multi_binding"))
-            .map(|attr| attr.parse_args::<TypeTraitObject>())
-            .collect::<syn::Result<Vec<_>>>()?;
+            .filter(|attr| {
+                attr.path().is_ident("from_multi_binding") || attr.path().is_ident("from_chain")
+            })
+            .collect::<Vec<_>>();
 
         let attr = match attrs.as_slice() {
             [] => return Ok(None),
@@ -110,16 +202,20 @@ impl DependentType {
             [_, second, ..] => {
                 return Err(syn::Error::new_spanned(
                     second,
-                    "Only one #[has_constructor] attribute is allowed",
+                    "Only one #[from_multi_binding] or #[from_chain] attribute is allowed",
                 ));
             }
         };
 
-        let output = Self::from_trait_object(attr)?;
-        Ok(Some(DependentType::CollectionOfTraitObjects(output)))
+        let output = Self::from_trait_object(&attr.parse_args::<TypeTraitObject>()?)?;
+        if attr.path().is_ident("from_chain") {
+            Ok(Some(DependentType::ChainOfTraitObjects(output)))
+        } else {
+            Ok(Some(DependentType::CollectionOfTraitObjects(output)))
+        }
     }
 
-    fn from_trait_object(trait_: &TypeTraitObject) -> syn::Result<Path> {
+    pub fn from_trait_object(trait_: &TypeTraitObject) -> syn::Result<TraitBounds> {
         let trait_bounds = trait_
             .bounds
             .iter()
@@ -155,16 +251,50 @@ impl DependentType {
             })
             .collect::<syn::Result<Vec<_>>>()?;
 
-        match trait_bounds.as_slice() {
-            [] => Err(syn::Error::new_spanned(
-                trait_,
-                error_messages::SIMPLE_TRAIT_BOUNDS_ONLY,
-            )),
-            [single] => Ok((*single).clone()),
-            [_, second, ..] => Err(syn::Error::new_spanned(
-                second,
-                error_messages::SIMPLE_TRAIT_BOUNDS_ONLY,
-            )),
+        // `dyn Send + Service` is the same type as `dyn Service + Send`, so find the main trait
+        let is_auto_trait = |path: &Path| {
+            path.segments.last().is_some_and(|segment| {
+                AUTO_TRAITS.iter().any(|auto_trait| segment.ident == auto_trait)
+            })
+        };
+        let (mut main_traits, mut auto_traits): (Vec<_>, Vec<_>) = trait_bounds
+            .into_iter()
+            .cloned()
+            .partition(|path| !is_auto_trait(path));
+        if main_traits.is_empty() && !auto_traits.is_empty() {
+            // Just `dyn Send`, say
+            main_traits.push(auto_traits.remove(0));
+        }
+        auto_traits.sort_by_key(|path| path.to_token_stream().to_string());
+
+        let main_trait = match main_traits.as_slice() {
+            [] => {
+                return Err(syn::Error::new_spanned(
+                    trait_,
+                    error_messages::SIMPLE_TRAIT_BOUNDS_ONLY,
+                ));
+            }
+            [single] => single.clone(),
+            [_, second, ..] => {
+                return Err(syn::Error::new_spanned(
+                    second,
+                    error_messages::SIMPLE_TRAIT_BOUNDS_ONLY,
+                ));
+            }
+        };
+
+        Ok(TraitBounds {
+            trait_: main_trait,
+            auto_traits,
+        })
+    }
+}
+
+impl ToTokens for TraitBounds {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.trait_.to_tokens(tokens);
+        for auto_trait in &self.auto_traits {
+            quote!(+ #auto_trait).to_tokens(tokens);
         }
     }
 }
@@ -185,14 +315,32 @@ impl Namespace {
         Namespace { inner, references }
     }
 
-    pub fn from_trait_impl(trait_: &Path, target: &DependentType) -> Self {
+    pub fn from_trait_impl(trait_: &TraitBounds, target: &DependentType) -> Self {
         let mut inner = String::new();
-        let target = match target {
-            DependentType::RegularType(path) => path.path.segments.iter(),
-            DependentType::TraitObject(path) => path.segments.iter(),
-            DependentType::CollectionOfTraitObjects(_) => unreachable!(),
+        Self::push_trait_bounds(&mut inner, trait_);
+        match target {
+            DependentType::RegularType(path) => Self::push_path(&mut inner, &path.path),
+            DependentType::TraitObject(bounds) => Self::push_trait_bounds(&mut inner, bounds),
+            _ => unreachable!(),
         };
-        for segment in trait_.segments.iter().chain(target) {
+
+        let references = trait_.trait_.span();
+        Namespace { inner, references }
+    }
+
+    fn push_trait_bounds(inner: &mut String, bounds: &TraitBounds) {
+        Self::push_path(inner, &bounds.trait_);
+        for auto_trait in &bounds.auto_traits {
+            inner.push_str("_and");
+            Self::push_path(inner, auto_trait);
+        }
+    }
+
+    /// Appends every segment of the path, including any type arguments, so that `Handler<Created>`
+    /// and `Handler<Deleted>` get different names. Arguments that aren't paths, like `&str` or
+    /// `[u8; 4]`, can't be spelled in an identifier, so they are appended as a hash instead.
+    fn push_path(inner: &mut String, path: &Path) {
+        for segment in &path.segments {
             if !inner.is_empty() {
                 inner.push('_');
             }
@@ -203,10 +351,50 @@ impl Namespace {
                     .from_case(Case::Pascal)
                     .to_case(Case::Snake),
             );
+
+            let PathArguments::AngleBracketed(generics) = &segment.arguments else {
+                continue;
+            };
+            for arg in &generics.args {
+                let ty = match arg {
+                    // Lifetimes don't change which type this is
+                    GenericArgument::Lifetime(_) => continue,
+                    GenericArgument::Type(ty) => ty,
+                    GenericArgument::AssocType(assoc) => &assoc.ty,
+                    other => {
+                        Self::push_hash(inner, other);
+                        continue;
+                    }
+                };
+                match ty {
+                    Type::Path(ty) if ty.qself.is_none() => {
+                        inner.push_str("_of");
+                        Self::push_path(inner, &ty.path);
+                    }
+                    other => Self::push_hash(inner, other),
+                }
+            }
         }
+    }
 
-        let references = trait_.span();
-        Namespace { inner, references }
+    /// FNV-1a, as the name has to come out the same every time the macro runs.
+    fn push_hash(inner: &mut String, tokens: &impl ToTokens) {
+        let hash = tokens
+            .to_token_stream()
+            .to_string()
+            .bytes()
+            .fold(0xcbf29ce484222325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+            });
+        inner.push_str(&format!("_of_{:016x}", hash));
+    }
+
+    pub fn with_suffix(&self, suffix: &str) -> Self {
+        let inner = format!("{}_{}", self.inner, suffix);
+        Namespace {
+            inner,
+            references: self.references,
+        }
     }
 
     pub fn name_of_create_fn(&self) -> Ident {
@@ -216,6 +404,13 @@ impl Namespace {
         )
     }
 
+    pub fn name_of_provided_type(&self) -> Ident {
+        Ident::new(
+            &format!("__injector_provided_{}", self.inner),
+            self.references,
+        )
+    }
+
     pub fn name_of_inject_meta_fn(&self) -> Ident {
         Ident::new(
             &format!("__injector_inject_meta_fn_{}", self.inner),
@@ -229,38 +424,140 @@ pub fn quote_inject_meta(
     ns: &Namespace,
     dependencies: impl Iterator<Item = syn::Result<DependentType>>,
 ) -> syn::Result<TokenStream> {
+    let (dependencies, collections) = quote_dependencies(dependencies)?;
+    quote_inject_meta_for_components(
+        &[type_name.into_token_stream()],
+        ns,
+        dependencies,
+        collections,
+    )
+}
+
+/// Quotes expressions that collect the type IDs of all the given dependencies into a `Vec`, and
+/// those of the ones that are collections of multi bindings into another.
+pub fn quote_dependencies(
+    dependencies: impl Iterator<Item = syn::Result<DependentType>>,
+) -> syn::Result<(TokenStream, TokenStream)> {
     let dependencies = dependencies.collect::<syn::Result<Vec<_>>>()?;
+    let collections = dependencies
+        .iter()
+        .filter(|dep| dep.is_collection())
+        .map(|dep| dep.quote_type_id());
+    let collections = quote!(::std::vec![#(#collections),*]);
     let dependencies = dependencies.iter().map(|dep| dep.quote_type_id());
-    let dependencies = quote!(::std::vec![#(#dependencies),*]);
+    Ok((quote!(::std::vec![#(#dependencies),*]), collections))
+}
+
+/// Like [`quote_inject_meta`], but for a create function that returns several components at once
+/// (for constructors that return a tuple). The dependencies, and the ones among them that are
+/// collections, are each given as an expression that evaluates to a `Vec<TypeId>`, see
+/// [`quote_dependencies`].
+pub fn quote_inject_meta_for_components(
+    type_names: &[TokenStream],
+    ns: &Namespace,
+    dependencies: TokenStream,
+    collections: TokenStream,
+) -> syn::Result<TokenStream> {
     let create_fn_name = ns.name_of_create_fn();
     let inject_meta_fn_name = ns.name_of_inject_meta_fn();
 
+    let (this, also_creates) = type_names
+        .split_first()
+        .expect("at least one component is created");
+    let name = match also_creates {
+        [] => quote!(#this),
+        _ => quote!((#(#type_names),*)),
+    };
+
     Ok(quote! {
         #[::injector::derive_api::linkme::distributed_slice(::injector::derive_api::INJECTION_REGISTRY)]
         #[linkme(crate = ::injector::derive_api::linkme)]
         fn #inject_meta_fn_name() -> ::injector::derive_api::InjectMeta {
             ::injector::derive_api::InjectMeta {
-                this: ::std::any::TypeId::of::<#type_name>(),
-                name: ::std::any::type_name::<#type_name>(),
+                this: ::std::any::TypeId::of::<#this>(),
+                name: ::std::any::type_name::<#name>(),
+                also_creates: ::std::vec![#(::std::any::TypeId::of::<#also_creates>()),*],
                 dependencies: #dependencies,
+                collections: #collections,
                 create: #create_fn_name,
                 is_multi_binding: false,
+                order: 0,
             }
         }
     })
 }
 
+pub fn quote_binding_meta(
+    trait_: &TraitBounds,
+    ns: &Namespace,
+    static_impl_type: impl ToTokens,
+    options: &BindingOptions,
+) -> TokenStream {
+    let inject_meta_fn = ns.name_of_inject_meta_fn();
+    let create_fn_name = ns.name_of_create_fn();
+    let quote_option = |value: Option<&LitStr>| match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    };
+    let BindingOptions {
+        is_multi_binding,
+        is_default,
+        ..
+    } = options;
+    let profile = quote_option(options.profile.as_ref());
+    let key = quote_option(options.key.as_ref());
+    let order = match &options.order {
+        Some(order) => quote!(#order),
+        None => quote!(0),
+    };
+    let decorates = if options.is_decorator {
+        quote! {
+            ::std::option::Option::Some(::injector::derive_api::Decoration {
+                input: ::std::any::TypeId::of::<::injector::derive_api::DecoratorInput<#static_impl_type>>(),
+                order: #order,
+            })
+        }
+    } else {
+        quote!(::std::option::Option::None)
+    };
+
+    quote! {
+        #[::injector::derive_api::linkme::distributed_slice(::injector::derive_api::BINDING_REGISTRY)]
+        #[linkme(crate = ::injector::derive_api::linkme)]
+        fn #inject_meta_fn() -> ::injector::derive_api::BindingMeta {
+            ::injector::derive_api::BindingMeta {
+                trait_object: ::std::any::TypeId::of::<&'static (dyn #trait_)>(),
+                name: ::std::any::type_name::<dyn #trait_>(),
+                impl_type: ::std::any::TypeId::of::<#static_impl_type>(),
+                is_multi_binding: #is_multi_binding,
+                order: #order,
+                is_default: #is_default,
+                decorates: #decorates,
+                profile: #profile,
+                key: #key,
+                create: #create_fn_name,
+            }
+        }
+    }
+}
+
 pub fn strip_lifetimes(path: &mut Path) {
     for segment in &mut path.segments.iter_mut() {
         let PathArguments::AngleBracketed(generics) = &mut segment.arguments else {
             continue;
         };
 
-        let old_args = mem::replace(&mut generics.args, Default::default());
+        let old_args = mem::take(&mut generics.args);
         generics.args.extend(
             old_args
                 .into_iter()
                 .filter(|arg| !matches!(arg, GenericArgument::Lifetime(_))),
         );
+        // Type arguments can have lifetimes of their own, like `Reloadable<'a, Limits<'a>>`
+        for arg in generics.args.iter_mut() {
+            if let GenericArgument::Type(Type::Path(inner)) = arg {
+                strip_lifetimes(&mut inner.path);
+            }
+        }
     }
 }
@@ -8,6 +8,8 @@ fn main() {
 
     assert_eq!(&raw const *everything.simple, &raw const *everything.type_with_constructor.simple);
     assert_eq!(&raw const *everything.simple, &raw const *everything.basic_type.simple);
+    assert_eq!(&raw const *everything.basic_type, &raw const *everything.type_with_associated_constructor.basic_type);
+    println!("{}", everything.type_with_associated_constructor.greeting);
     println!("Hello, {}", everything.type_with_constructor.custom_field);
 }
 
@@ -27,7 +29,7 @@ struct TypeWithConstructor<'a> {
 }
 
 #[constructor]
-fn build_type_with_constructor(simple: &SimplestObject) -> TypeWithConstructor {
+fn build_type_with_constructor(simple: &SimplestObject) -> TypeWithConstructor<'_> {
     let custom_field = env::var("USER").unwrap_or_else(|_| String::new());
     TypeWithConstructor { simple, custom_field }
 }
@@ -37,4 +39,21 @@ struct Everything<'a> {
     simple: &'a SimplestObject,
     basic_type: &'a BasicType<'a>,
     type_with_constructor: &'a TypeWithConstructor<'a>,
+    type_with_associated_constructor: &'a TypeWithAssociatedConstructor<'a>,
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+struct TypeWithAssociatedConstructor<'a> {
+    basic_type: &'a BasicType<'a>,
+    greeting: String,
+}
+
+#[constructor]
+impl<'a> TypeWithAssociatedConstructor<'a> {
+    #[constructor]
+    fn new(basic_type: &'a BasicType<'a>) -> Self {
+        let greeting = "Hello from an associated constructor".to_string();
+        TypeWithAssociatedConstructor { basic_type, greeting }
+    }
 }
@@ -0,0 +1,82 @@
+use std::cell::RefCell;
+
+use injector::{Injectable, Injector};
+
+fn main() {
+    let injector = Injector::new();
+    assert_eq!(injector.get::<Reports>().title(), "reports for everyone");
+
+    {
+        // Each tenant gets its own scope, which overrides the tenant and rebuilds what uses it
+        let acme = injector
+            .child()
+            .inject_value(Tenant { name: "acme".to_string() })
+            .construct::<Reports>()
+            .construct::<Connection>()
+            .build();
+        assert_eq!(acme.get::<Reports>().title(), "reports for acme");
+        assert_eq!(acme.get::<Connection>().tenant.name, "acme");
+
+        // Everything else comes straight from the parent
+        let database = &raw const *injector.get::<Database>();
+        assert_eq!(&raw const *acme.get::<Database>(), database);
+        assert_eq!(&raw const *acme.get::<Connection>().database, database);
+        assert_eq!(injector.get::<Reports>().title(), "reports for everyone");
+    }
+
+    // Only the child's own components were dropped, and the newest went first
+    let teardown: &Teardown = injector.get();
+    assert_eq!(*teardown.dropped.borrow(), ["reports for acme", "connection for acme"]);
+    println!("Dropped {}", teardown.dropped.borrow().join(", then "));
+}
+
+#[derive(Injectable)]
+struct Database;
+
+#[derive(Injectable)]
+struct Teardown {
+    #[inject(default)]
+    dropped: RefCell<Vec<String>>,
+}
+
+#[derive(Injectable)]
+struct Tenant {
+    #[inject(with = everyone)]
+    name: String,
+}
+
+fn everyone() -> String {
+    "everyone".to_string()
+}
+
+#[derive(Injectable)]
+struct Connection<'a> {
+    database: &'a Database,
+    tenant: &'a Tenant,
+    teardown: &'a Teardown,
+}
+
+impl Drop for Connection<'_> {
+    fn drop(&mut self) {
+        let dropped = format!("connection for {}", self.tenant.name);
+        self.teardown.dropped.borrow_mut().push(dropped);
+    }
+}
+
+#[derive(Injectable)]
+struct Reports<'a> {
+    connection: &'a Connection<'a>,
+}
+
+impl Reports<'_> {
+    fn title(&self) -> String {
+        format!("reports for {}", self.connection.tenant.name)
+    }
+}
+
+impl Drop for Reports<'_> {
+    fn drop(&mut self) {
+        let dropped = self.title();
+        self.connection.teardown.dropped.borrow_mut().push(dropped);
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Parser;
+use injector::{Injectable, Injector};
+
+fn main() {
+    // A real program would call `.with_args::<Cli>()`, which parses `std::env::args`
+    let injector = Injector::builder()
+        .try_with_args_from::<Cli, _, _>(["server", "--port", "9000", "--verbose"])
+        .unwrap()
+        .build_the_world();
+    let server: &Server = injector.get();
+
+    assert_eq!(server.cli.port, 9000);
+    assert!(server.cli.verbose);
+    println!("Listening on port {}", server.cli.port);
+
+    // Bad arguments are reported before any constructor runs
+    let error = Injector::builder()
+        .try_with_args_from::<Cli, _, _>(["server", "--port", "not a port"])
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+}
+
+#[derive(Parser, Injectable)]
+#[has_constructor] // Parsed by the builder, in `with_args`
+struct Cli {
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+    #[arg(long)]
+    verbose: bool,
+}
+
+#[derive(Injectable)]
+struct Server<'a> {
+    cli: &'a Cli,
+}
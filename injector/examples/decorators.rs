@@ -0,0 +1,68 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use injector::{Injectable, Injector, binding, decorates};
+
+fn main() {
+    let injector = Injector::new();
+    let users: &UserService = injector.get();
+
+    assert_eq!(users.repository.find(1), "[logged] user 1 from the database");
+    assert_eq!(users.repository.find(1), "[logged] user 1 from the cache");
+    println!("{}", users.repository.find(2));
+
+    // The decorated binding is what the injector hands out directly, too
+    let repository = injector.get_trait_object::<dyn Repository>();
+    assert_eq!(repository.find(2), "[logged] user 2 from the cache");
+}
+
+trait Repository {
+    fn find(&self, id: u32) -> String;
+}
+
+#[derive(Injectable)]
+struct DatabaseRepository;
+
+#[binding]
+impl Repository for DatabaseRepository {
+    fn find(&self, id: u32) -> String {
+        format!("user {} from the database", id)
+    }
+}
+
+#[derive(Injectable)]
+struct CachingRepository<'a> {
+    inner: &'a dyn Repository,
+    #[inject(default)]
+    cache: RefCell<HashMap<u32, String>>,
+}
+
+// Lower orders are closer to the binding, so this caches the database...
+#[decorates(dyn Repository, order = 1)]
+impl Repository for CachingRepository<'_> {
+    fn find(&self, id: u32) -> String {
+        if let Some(cached) = self.cache.borrow().get(&id) {
+            return cached.replace("database", "cache");
+        }
+        let found = self.inner.find(id);
+        self.cache.borrow_mut().insert(id, found.clone());
+        found
+    }
+}
+
+#[derive(Injectable)]
+struct LoggingRepository<'a> {
+    inner: &'a dyn Repository,
+}
+
+// ...and this logs everything, including cache hits
+#[decorates(dyn Repository, order = 2)]
+impl Repository for LoggingRepository<'_> {
+    fn find(&self, id: u32) -> String {
+        format!("[logged] {}", self.inner.find(id))
+    }
+}
+
+#[derive(Injectable)]
+struct UserService<'a> {
+    repository: &'a dyn Repository,
+}
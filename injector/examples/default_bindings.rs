@@ -0,0 +1,67 @@
+use injector::{Injectable, Injector, binding};
+
+use crate::library::{Clock, Logger};
+
+fn main() {
+    let injector = Injector::new();
+    let report: &Report = injector.get();
+
+    // The application's clock replaces the library's, but the library's logger is still used
+    assert_eq!(report.clock.now(), 1_700_000_000);
+    assert_eq!(report.logger.log("generated"), "[library] generated");
+    println!("{}", report.logger.log(&format!("generated at {}", report.clock.now())));
+}
+
+/// Pretend this is a separate crate, which ships implementations that an application can replace.
+mod library {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use injector::{Injectable, default_binding};
+
+    pub trait Clock {
+        fn now(&self) -> u64;
+    }
+
+    pub trait Logger {
+        fn log(&self, message: &str) -> String;
+    }
+
+    #[derive(Injectable)]
+    pub struct SystemClock;
+
+    #[default_binding]
+    impl Clock for SystemClock {
+        fn now(&self) -> u64 {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        }
+    }
+
+    #[derive(Injectable)]
+    pub struct LibraryLogger;
+
+    #[default_binding]
+    impl Logger for LibraryLogger {
+        fn log(&self, message: &str) -> String {
+            format!("[library] {}", message)
+        }
+    }
+}
+
+#[derive(Injectable)]
+struct FixedClock;
+
+#[binding]
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        1_700_000_000
+    }
+}
+
+#[derive(Injectable)]
+struct Report<'a> {
+    clock: &'a dyn Clock,
+    logger: &'a dyn Logger,
+}
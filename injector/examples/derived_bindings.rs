@@ -0,0 +1,76 @@
+use std::fmt::{self, Debug, Display};
+
+use injector::{Injectable, Injector};
+
+fn main() {
+    let injector = Injector::new();
+    let app: &App = injector.get();
+
+    assert_eq!(app.version.to_string(), "v1.4.2");
+    assert_eq!(app.described.describe(), "Version([1, 4, 2])");
+    let plugins = app.plugins.iter().map(|plugin| plugin.name()).collect::<Vec<_>>();
+    assert_eq!(plugins.len(), 2);
+    assert!(plugins.contains(&"audit") && plugins.contains(&"metrics"));
+
+    println!("Running {} with plugins {:?}", app.version, plugins);
+}
+
+// None of these impls can carry a #[binding], so the types list their traits instead
+#[derive(Injectable, Debug)]
+#[binds(dyn Display, dyn Describe)]
+struct Version(#[inject(with = current_version)] [u32; 3]);
+
+fn current_version() -> [u32; 3] {
+    [1, 4, 2]
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [major, minor, patch] = self.0;
+        write!(f, "v{}.{}.{}", major, minor, patch)
+    }
+}
+
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+// A blanket impl
+impl<T: Debug> Describe for T {
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+trait Plugin {
+    fn name(&self) -> &'static str;
+}
+
+// Impls from a macro
+macro_rules! plugin {
+    ($type:ident, $name:literal) => {
+        impl Plugin for $type {
+            fn name(&self) -> &'static str {
+                $name
+            }
+        }
+    };
+}
+
+#[derive(Injectable)]
+#[binds(multi(dyn Plugin))]
+struct AuditPlugin;
+plugin!(AuditPlugin, "audit");
+
+#[derive(Injectable)]
+#[binds(multi(dyn Plugin))]
+struct MetricsPlugin;
+plugin!(MetricsPlugin, "metrics");
+
+#[derive(Injectable)]
+struct App<'a> {
+    version: &'a dyn Display,
+    described: &'a dyn Describe,
+    #[from_multi_binding(dyn Plugin)]
+    plugins: Vec<&'a dyn Plugin>,
+}
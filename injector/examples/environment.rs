@@ -0,0 +1,41 @@
+use std::env;
+
+use injector::{BuildError, ComponentError, Injectable, Injector};
+
+fn main() {
+    // SAFETY: This example is single threaded
+    unsafe { env::set_var("EXAMPLE_PORT", "not a port") };
+    let error = Injector::builder().try_build_the_world().err().unwrap();
+    let BuildError::Component {
+        error: ComponentError::InvalidEnvironmentVariable { variable, .. },
+        ..
+    } = &error
+    else {
+        panic!("Unexpected error {}", error)
+    };
+    assert_eq!(*variable, "EXAMPLE_PORT");
+    println!("{}", error);
+
+    // SAFETY: This example is single threaded
+    unsafe { env::set_var("EXAMPLE_PORT", "9000") };
+    let injector = Injector::new();
+    let settings: &Settings = injector.get();
+
+    assert_eq!(settings.port, 9000);
+    assert_eq!(settings.host, "localhost");
+    assert_eq!(settings.retries, -1);
+    assert_eq!(settings.separator, ',');
+    println!("Listening on {}:{}", settings.host, settings.port);
+}
+
+#[derive(Injectable)]
+struct Settings {
+    #[inject(env = "EXAMPLE_PORT", default = 8080)]
+    port: u16,
+    #[inject(env = "EXAMPLE_HOST", default = "localhost")]
+    host: String,
+    #[inject(env = "EXAMPLE_RETRIES", default = -1i32)]
+    retries: i32,
+    #[inject(env = "EXAMPLE_SEPARATOR", default = ',')]
+    separator: char,
+}
@@ -0,0 +1,83 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use injector::{Injectable, Injector, binding};
+
+fn main() {
+    let injector = Injector::builder()
+        .inject_foreign(Duration::from_secs(30))
+        .inject_value(Verbose(true))
+        .build_the_world();
+    let service: &Service = injector.get();
+
+    assert_eq!(service.timeout, Duration::from_secs(30));
+    assert!(service.verbose.0);
+    assert_eq!(service.lookup("home"), "Welcome home");
+    assert_eq!(service.lookup("home"), "Welcome home");
+    assert_eq!(*service.lookups.lock().unwrap(), 2);
+    assert_eq!(service.banner, "Welcome home (cached for 30s)");
+    println!("{} lookups against {} pages", service.lookups.lock().unwrap(), service.cache.len());
+}
+
+#[derive(Injectable)]
+struct Pages;
+
+trait Home {
+    fn home(&self) -> &'static str;
+}
+
+#[binding]
+impl Home for Pages {
+    fn home(&self) -> &'static str {
+        "home"
+    }
+}
+
+#[derive(Injectable, Clone, Copy)]
+#[has_constructor]
+struct Verbose(bool);
+
+impl Pages {
+    fn all(&self) -> impl Iterator<Item = (&'static str, &'static str)> {
+        [("home", "Welcome home"), ("about", "About us")].into_iter()
+    }
+}
+
+// Not every field has to be a reference to another component. Without #[inject] here, this struct
+// would need a hand written #[constructor].
+#[derive(Injectable)]
+struct Service<'a> {
+    pages: &'a Pages,
+    #[inject(default)]
+    lookups: Mutex<usize>,
+    #[inject(with = build_cache)]
+    cache: HashMap<&'static str, &'static str>,
+    // The arguments can be components, foreign types or trait objects, like any other dependency
+    #[inject(with = build_banner)]
+    banner: String,
+    // Small components can be taken by value rather than by reference
+    #[inject(clone)]
+    timeout: Duration,
+    #[inject(clone)]
+    verbose: Verbose,
+}
+
+fn build_cache(pages: &Pages) -> HashMap<&'static str, &'static str> {
+    pages.all().collect()
+}
+
+fn build_banner(home: &dyn Home, pages: &Pages, timeout: &Duration) -> String {
+    let (_, content) = pages.all().find(|(name, _)| *name == home.home()).unwrap();
+    format!("{} (cached for {}s)", content, timeout.as_secs())
+}
+
+impl Service<'_> {
+    fn lookup(&self, page: &str) -> &'static str {
+        *self.lookups.lock().unwrap() += 1;
+        self.cache.get(page).copied().unwrap_or_else(|| {
+            self.pages
+                .all()
+                .find(|(name, _)| *name == page)
+                .map_or("Not found", |(_, content)| content)
+        })
+    }
+}
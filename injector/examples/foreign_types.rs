@@ -0,0 +1,35 @@
+use std::{net::SocketAddr, sync::mpsc, thread};
+
+use injector::{Injectable, Injector, provides};
+
+fn main() {
+    let listen_on: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let injector = Injector::builder()
+        .inject_foreign(listen_on)
+        .build_the_world();
+    let everything: &Everything = injector.get();
+
+    assert_eq!(everything.listen_on, &listen_on);
+    everything.log_sink.send("Hello, world".to_string()).unwrap();
+    println!("Listening on {}", everything.listen_on);
+}
+
+// Neither SocketAddr nor mpsc::Sender can derive Injectable, as they are defined in std. They can
+// still be injected or provided, and then depended upon like any other component.
+#[provides]
+fn make_log_sink(listen_on: &SocketAddr) -> mpsc::Sender<String> {
+    let prefix = listen_on.to_string();
+    let (sender, receiver) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for line in receiver {
+            println!("[{}] {}", prefix, line);
+        }
+    });
+    sender
+}
+
+#[derive(Injectable)]
+struct Everything<'a> {
+    listen_on: &'a SocketAddr,
+    log_sink: &'a mpsc::Sender<String>,
+}
@@ -0,0 +1,51 @@
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    thread,
+};
+
+use injector::{Injectable, Injector, binding, global, global::GlobalError, inject};
+
+fn main() {
+    assert!(matches!(global::try_get::<Metrics>(), Err(GlobalError::NotInstalled)));
+
+    global::install(Injector::new()).unwrap();
+    assert!(matches!(global::install(Injector::new()), Err(GlobalError::AlreadyInstalled)));
+
+    // Pretend these are callbacks from C, which can't be given a reference to anything
+    let callbacks = (0..4)
+        .map(|_| thread::spawn(|| on_request_finished()))
+        .collect::<Vec<_>>();
+    for callback in callbacks {
+        callback.join().unwrap();
+    }
+
+    assert_eq!(inject!(Metrics).requests.load(Ordering::SeqCst), 4);
+    assert_eq!(inject!(dyn Reporter + Sync).report(), "4 requests");
+    println!("{}", inject!(dyn Reporter + Sync).report());
+}
+
+extern "C" fn on_request_finished() {
+    inject!(Metrics).requests.fetch_add(1, Ordering::SeqCst);
+}
+
+#[derive(Injectable)]
+struct Metrics {
+    #[inject(default)]
+    requests: AtomicU32,
+}
+
+trait Reporter {
+    fn report(&self) -> String;
+}
+
+#[derive(Injectable)]
+struct MetricsReporter<'a> {
+    metrics: &'a Metrics,
+}
+
+#[binding(bounds = Sync)]
+impl Reporter for MetricsReporter<'_> {
+    fn report(&self) -> String {
+        format!("{} requests", self.metrics.requests.load(Ordering::SeqCst))
+    }
+}
@@ -0,0 +1,95 @@
+use std::{cell::RefCell, time::Duration};
+
+use injector::{
+    Injectable, Injector, Interceptor, MethodCall, Rejection, binding, interceptable, multi_binding,
+};
+
+fn main() {
+    let injector = Injector::new();
+    let orders: &OrderService = injector.get();
+
+    assert_eq!(orders.payments.charge(1, 250), "charged order 1 for 250");
+    assert_eq!(orders.payments.refund(1), "refunded order 1");
+
+    let log: &CallLog = injector.get();
+    assert_eq!(
+        *log.calls.borrow(),
+        [
+            "before charge",
+            "after charge",
+            "before refund",
+            "after refund",
+        ]
+    );
+
+    let metrics: &Metrics = injector.get();
+    assert_eq!(metrics.timings.borrow().len(), 2);
+    for (method, elapsed) in metrics.timings.borrow().iter() {
+        println!("{} took {:?}", method, elapsed);
+    }
+}
+
+// Intercepted bindings must implement every method of the trait, which this lets them check
+#[interceptable]
+trait PaymentGateway {
+    fn charge(&self, order: u32, amount: u64) -> String;
+    fn refund(&self, order: u32) -> String;
+}
+
+#[derive(Injectable)]
+struct StripeGateway;
+
+// Callers get a proxy that runs every interceptor around each of these methods
+#[binding(intercept)]
+impl PaymentGateway for StripeGateway {
+    fn charge(&self, order: u32, amount: u64) -> String {
+        format!("charged order {} for {}", order, amount)
+    }
+
+    fn refund(&self, order: u32) -> String {
+        format!("refunded order {}", order)
+    }
+}
+
+#[derive(Injectable)]
+struct CallLog {
+    #[inject(default)]
+    calls: RefCell<Vec<String>>,
+}
+
+#[derive(Injectable)]
+struct LoggingInterceptor<'a> {
+    log: &'a CallLog,
+}
+
+#[multi_binding]
+impl Interceptor for LoggingInterceptor<'_> {
+    fn before(&self, call: &MethodCall) -> Result<(), Rejection> {
+        self.log.calls.borrow_mut().push(format!("before {}", call.method));
+        Ok(())
+    }
+
+    fn after(&self, call: &MethodCall, _elapsed: Duration) {
+        self.log.calls.borrow_mut().push(format!("after {}", call.method));
+    }
+}
+
+#[derive(Injectable)]
+struct Metrics {
+    #[inject(default)]
+    timings: RefCell<Vec<(&'static str, Duration)>>,
+}
+
+// Interceptors only need to implement the half they care about. A higher order runs closer to the
+// call, so the timings leave out the time spent logging.
+#[multi_binding(order = 1)]
+impl Interceptor for Metrics {
+    fn after(&self, call: &MethodCall, elapsed: Duration) {
+        self.timings.borrow_mut().push((call.method, elapsed));
+    }
+}
+
+#[derive(Injectable)]
+struct OrderService<'a> {
+    payments: &'a dyn PaymentGateway,
+}
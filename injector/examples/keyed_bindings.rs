@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use injector::{BuildError, Injectable, Injector, binding};
+
+static CONNECTED_TO_S3: AtomicBool = AtomicBool::new(false);
+
+fn main() {
+    let injector = Injector::builder()
+        .select::<dyn Storage>("local")
+        .build_the_world();
+    let uploads: &Uploads = injector.get();
+    assert_eq!(uploads.storage.put("avatar.png"), "/var/data/avatar.png");
+    println!("{}", uploads.storage.put("avatar.png"));
+
+    // The S3 client is only needed by the S3 storage, so it was never created
+    assert!(!CONNECTED_TO_S3.load(Ordering::SeqCst));
+
+    let injector = Injector::builder()
+        .select::<dyn Storage>("s3")
+        .build_the_world();
+    let uploads: &Uploads = injector.get();
+    assert_eq!(uploads.storage.put("avatar.png"), "s3://uploads/avatar.png");
+    assert!(CONNECTED_TO_S3.load(Ordering::SeqCst));
+
+    let error = Injector::builder()
+        .select::<dyn Storage>("ftp")
+        .try_build_the_world()
+        .err()
+        .unwrap();
+    let BuildError::UnknownBindingKey { valid_keys, .. } = &error else {
+        panic!("Unexpected error {}", error)
+    };
+    assert_eq!(*valid_keys, ["local", "s3"]);
+    println!("{}", error);
+}
+
+trait Storage {
+    fn put(&self, name: &str) -> String;
+}
+
+#[derive(Injectable)]
+struct S3Client {
+    #[inject(with = connect_to_s3)]
+    bucket: String,
+}
+
+fn connect_to_s3() -> String {
+    CONNECTED_TO_S3.store(true, Ordering::SeqCst);
+    "uploads".to_string()
+}
+
+#[derive(Injectable)]
+struct S3Storage<'a> {
+    client: &'a S3Client,
+}
+
+#[binding(key = "s3")]
+impl Storage for S3Storage<'_> {
+    fn put(&self, name: &str) -> String {
+        format!("s3://{}/{}", self.client.bucket, name)
+    }
+}
+
+#[derive(Injectable)]
+struct LocalStorage;
+
+#[binding(key = "local")]
+impl Storage for LocalStorage {
+    fn put(&self, name: &str) -> String {
+        format!("/var/data/{}", name)
+    }
+}
+
+#[derive(Injectable)]
+struct Uploads<'a> {
+    storage: &'a dyn Storage,
+}
@@ -0,0 +1,114 @@
+use std::env;
+
+use injector::{
+    BuildError, ComponentError, Injectable, Injector, binding,
+    config::{Config, ConfigLoader},
+};
+use serde::Deserialize;
+
+const DEFAULTS: &str = r#"
+[database]
+url = "postgres://localhost/app"
+pool_size = 5
+
+[http]
+port = 8080
+
+[cache]
+backend = "memory"
+"#;
+
+fn main() {
+    // SAFETY: This example is single threaded
+    unsafe { env::set_var("EXAMPLE__DATABASE__POOL_SIZE", "20") };
+    let injector = Injector::builder()
+        .with_config(loader().load().unwrap())
+        .select_from_config::<dyn Cache>("cache.backend")
+        .build_the_world();
+    let server: &Server = injector.get();
+
+    // The defaults come from DEFAULTS, and the pool size is overridden by the environment
+    assert_eq!(server.database.url, "postgres://localhost/app");
+    assert_eq!(server.database.pool_size, 20);
+    assert_eq!(server.http.port, 8080);
+    assert_eq!(injector.get_trait_object::<dyn Cache>().describe(), "in memory");
+    println!(
+        "Serving on port {} with {} database connections",
+        server.http.port, server.database.pool_size
+    );
+
+    // Validation failures come back from the builder
+    let config = loader().json(r#"{ "http": { "port": 0 } }"#).load().unwrap();
+    let error = Injector::builder()
+        .with_config(config)
+        .try_build_the_world()
+        .err()
+        .unwrap();
+    let BuildError::Component {
+        error: ComponentError::InvalidConfig { section, .. },
+        ..
+    } = &error
+    else {
+        panic!("Unexpected error {}", error)
+    };
+    assert_eq!(*section, "http");
+    println!("{}", error);
+}
+
+fn loader() -> ConfigLoader {
+    let environment = env::var("EXAMPLE_ENVIRONMENT").unwrap_or_else(|_| "dev".to_string());
+    Config::loader()
+        .toml(DEFAULTS)
+        .optional_file(format!("config/{}.toml", environment))
+        .env_prefix("EXAMPLE")
+}
+
+#[derive(Deserialize, Injectable)]
+#[config(section = "database")]
+struct DatabaseConfig {
+    url: String,
+    pool_size: u32,
+}
+
+#[derive(Deserialize, Injectable)]
+#[config(section = "http", validate = validate_http)]
+struct HttpConfig {
+    port: u16,
+}
+
+fn validate_http(config: &HttpConfig) -> Result<(), String> {
+    if config.port == 0 {
+        return Err("port must not be 0".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Injectable)]
+struct Server<'a> {
+    database: &'a DatabaseConfig,
+    http: &'a HttpConfig,
+}
+
+trait Cache {
+    fn describe(&self) -> &'static str;
+}
+
+#[derive(Injectable)]
+struct MemoryCache;
+
+#[binding(key = "memory")]
+impl Cache for MemoryCache {
+    fn describe(&self) -> &'static str {
+        "in memory"
+    }
+}
+
+#[derive(Injectable)]
+struct RedisCache;
+
+#[binding(key = "redis")]
+impl Cache for RedisCache {
+    fn describe(&self) -> &'static str {
+        "redis"
+    }
+}
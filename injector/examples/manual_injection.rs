@@ -23,6 +23,6 @@ struct Everything<'a> {
 }
 
 #[constructor]
-fn make_everything(manually: &ManuallyInjectedValue) -> Everything {
+fn make_everything(manually: &ManuallyInjectedValue) -> Everything<'_> {
     Everything { manually }
 }
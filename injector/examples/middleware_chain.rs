@@ -0,0 +1,81 @@
+use injector::{Chain, Injectable, Injector, Next, multi_binding};
+
+fn main() {
+    let injector = Injector::new();
+    let server: &Server = injector.get();
+
+    assert_eq!(server.middleware.len(), 3);
+    assert_eq!(
+        server.handle("/orders", Some("admin")),
+        "[logged] 200 /orders for admin"
+    );
+    assert_eq!(server.handle("/orders", None), "[logged] 401");
+    assert_eq!(server.handle("/health", None), "200 /health");
+    println!("{}", server.handle("/orders", Some("someone")));
+}
+
+struct Request<'a> {
+    path: &'a str,
+    user: Option<&'a str>,
+}
+
+trait Middleware {
+    fn handle(&self, request: &Request, next: Next<'_, dyn Middleware>) -> String;
+}
+
+// Runs the rest of the chain, and then the endpoint once every middleware has had its turn
+fn run(next: Next<'_, dyn Middleware>, request: &Request) -> String {
+    match next.pop() {
+        Some((middleware, next)) => middleware.handle(request, next),
+        None => format!("200 {} for {}", request.path, request.user.unwrap_or("anyone")),
+    }
+}
+
+#[derive(Injectable)]
+struct HealthCheck;
+
+// Lower orders run first, so health checks skip logging and authentication entirely
+#[multi_binding(order = -1)]
+impl Middleware for HealthCheck {
+    fn handle(&self, request: &Request, next: Next<'_, dyn Middleware>) -> String {
+        if request.path == "/health" {
+            return "200 /health".to_string();
+        }
+        run(next, request)
+    }
+}
+
+#[derive(Injectable)]
+struct Authentication;
+
+#[multi_binding(order = 2)]
+impl Middleware for Authentication {
+    fn handle(&self, request: &Request, next: Next<'_, dyn Middleware>) -> String {
+        match request.user {
+            Some(_) => run(next, request),
+            None => "401".to_string(),
+        }
+    }
+}
+
+#[derive(Injectable)]
+struct Logging;
+
+#[multi_binding(order = 1)]
+impl Middleware for Logging {
+    fn handle(&self, request: &Request, next: Next<'_, dyn Middleware>) -> String {
+        format!("[logged] {}", run(next, request))
+    }
+}
+
+#[derive(Injectable)]
+struct Server<'a> {
+    #[from_chain(dyn Middleware)]
+    middleware: Chain<'a, dyn Middleware>,
+}
+
+impl Server<'_> {
+    fn handle(&self, path: &str, user: Option<&str>) -> String {
+        run(self.middleware.start(), &Request { path, user })
+    }
+}
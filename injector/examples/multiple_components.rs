@@ -0,0 +1,80 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+};
+
+use injector::{Injectable, Injector, constructor, provides};
+
+fn main() {
+    let injector = Injector::new();
+    let everything: &Everything = injector.get();
+
+    everything.jobs.send("first job").unwrap();
+    assert_eq!(everything.worker.queue.recv(), Ok("first job"));
+
+    assert!(everything.server.is_running());
+    everything.shutdown.trigger();
+    assert!(!everything.server.is_running());
+    println!("Server stopped");
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+struct ServerState {
+    running: AtomicBool,
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+struct ServerHandle<'a> {
+    state: &'a ServerState,
+}
+
+impl ServerHandle<'_> {
+    fn is_running(&self) -> bool {
+        self.state.running.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+struct ShutdownTrigger<'a> {
+    state: &'a ServerState,
+}
+
+impl ShutdownTrigger<'_> {
+    fn trigger(&self) {
+        self.state.running.store(false, Ordering::SeqCst)
+    }
+}
+
+#[constructor]
+fn make_server_state() -> ServerState {
+    ServerState {
+        running: AtomicBool::new(true),
+    }
+}
+
+// Both halves are created by one call, and each becomes its own component.
+#[constructor]
+fn make_server(state: &ServerState) -> (ServerHandle<'_>, ShutdownTrigger<'_>) {
+    (ServerHandle { state }, ShutdownTrigger { state })
+}
+
+#[provides]
+fn make_job_queue() -> (mpsc::Sender<&'static str>, mpsc::Receiver<&'static str>) {
+    mpsc::channel()
+}
+
+#[derive(Injectable)]
+struct Worker<'a> {
+    queue: &'a mpsc::Receiver<&'static str>,
+}
+
+#[derive(Injectable)]
+struct Everything<'a> {
+    server: &'a ServerHandle<'a>,
+    shutdown: &'a ShutdownTrigger<'a>,
+    jobs: &'a mpsc::Sender<&'static str>,
+    worker: &'a Worker<'a>,
+}
@@ -0,0 +1,60 @@
+use injector::{Injectable, Injector, binding};
+
+fn main() {
+    let injector = Injector::builder().profile("prod").build_the_world();
+    let signup: &Signup = injector.get();
+    assert_eq!(signup.mailer.send("welcome"), "smtp: welcome");
+    println!("{}", signup.mailer.send("welcome"));
+
+    let injector = Injector::builder().profile("test").build_the_world();
+    let signup: &Signup = injector.get();
+    assert_eq!(signup.mailer.send("welcome"), "recorded: welcome");
+
+    // Without a matching profile, the unprofiled binding is the default
+    for injector in [
+        Injector::new(),
+        Injector::builder().profile("staging").build_the_world(),
+    ] {
+        let signup: &Signup = injector.get();
+        assert_eq!(signup.mailer.send("welcome"), "logged: welcome");
+    }
+}
+
+trait Mailer {
+    fn send(&self, message: &str) -> String;
+}
+
+#[derive(Injectable)]
+struct SmtpMailer;
+
+#[binding(profile = "prod")]
+impl Mailer for SmtpMailer {
+    fn send(&self, message: &str) -> String {
+        format!("smtp: {}", message)
+    }
+}
+
+#[derive(Injectable)]
+struct RecordingMailer;
+
+#[binding(profile = "test")]
+impl Mailer for RecordingMailer {
+    fn send(&self, message: &str) -> String {
+        format!("recorded: {}", message)
+    }
+}
+
+#[derive(Injectable)]
+struct LoggingMailer;
+
+#[binding]
+impl Mailer for LoggingMailer {
+    fn send(&self, message: &str) -> String {
+        format!("logged: {}", message)
+    }
+}
+
+#[derive(Injectable)]
+struct Signup<'a> {
+    mailer: &'a dyn Mailer,
+}
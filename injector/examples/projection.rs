@@ -0,0 +1,50 @@
+use injector::{Injectable, Injector};
+
+fn main() {
+    let config = Config {
+        database: DatabaseConfig {
+            url: "postgres://localhost/app".to_string(),
+        },
+        http: HttpConfig { port: 8080 },
+    };
+    let injector = Injector::builder().inject_value(config).build_the_world();
+
+    let config: &Config = injector.get();
+    let database: &Database = injector.get();
+    let server: &Server = injector.get();
+
+    // No copies are made, the projected fields point straight into the Config component
+    assert_eq!(&raw const *database.config, &raw const config.database);
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.config.port, server.port);
+    println!("Connecting to {} and listening on {}", database.config.url, server.port);
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Config {
+    database: DatabaseConfig,
+    http: HttpConfig,
+}
+
+struct DatabaseConfig {
+    url: String,
+}
+
+struct HttpConfig {
+    port: u16,
+}
+
+#[derive(Injectable)]
+struct Database<'a> {
+    #[inject(from = Config, path = database)]
+    config: &'a DatabaseConfig,
+}
+
+#[derive(Injectable)]
+struct Server<'a> {
+    #[inject(from = Config, path = http)]
+    config: &'a HttpConfig,
+    #[inject(from = Config, path = http.port, clone)]
+    port: u16,
+}
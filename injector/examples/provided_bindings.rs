@@ -0,0 +1,84 @@
+use injector::{Injectable, Injector, provides};
+
+fn main() {
+    let injector = Injector::builder()
+        .inject_value(Config { in_memory: true })
+        .build_the_world();
+    let everything: &Everything = injector.get();
+
+    assert_eq!(everything.storage.describe(), "in memory storage");
+    let mut plugins = everything
+        .plugins
+        .iter()
+        .map(|plugin| plugin.name())
+        .collect::<Vec<_>>();
+    plugins.sort();
+    assert_eq!(plugins, ["audit", "metrics"]);
+    println!("Using {} with plugins {:?}", everything.storage.describe(), plugins);
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Config {
+    in_memory: bool,
+}
+
+trait Storage {
+    fn describe(&self) -> String;
+}
+
+struct InMemoryStorage;
+
+impl Storage for InMemoryStorage {
+    fn describe(&self) -> String {
+        "in memory storage".to_string()
+    }
+}
+
+struct DiskStorage;
+
+impl Storage for DiskStorage {
+    fn describe(&self) -> String {
+        "disk storage".to_string()
+    }
+}
+
+// The implementation is chosen at runtime, so there is no single concrete type to put #[binding]
+// on. Returning a Box<dyn Storage> binds the result to dyn Storage directly.
+#[provides]
+fn make_storage(config: &Config) -> Box<dyn Storage> {
+    if config.in_memory {
+        Box::new(InMemoryStorage)
+    } else {
+        Box::new(DiskStorage)
+    }
+}
+
+trait Plugin {
+    fn name(&self) -> &'static str;
+}
+
+struct NamedPlugin(&'static str);
+
+impl Plugin for NamedPlugin {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+#[provides(multi)]
+fn make_audit_plugin() -> Box<dyn Plugin> {
+    Box::new(NamedPlugin("audit"))
+}
+
+#[provides(multi)]
+fn make_metrics_plugin() -> Box<dyn Plugin> {
+    Box::new(NamedPlugin("metrics"))
+}
+
+#[derive(Injectable)]
+struct Everything<'a> {
+    storage: &'a dyn Storage,
+    #[from_multi_binding(dyn Plugin)]
+    plugins: Vec<&'a dyn Plugin>,
+}
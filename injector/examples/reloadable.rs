@@ -0,0 +1,60 @@
+use std::{env, fs, path::PathBuf};
+
+use injector::{Injectable, Injector, Reloadable};
+
+fn main() {
+    fs::write(limits_file(), "100").unwrap();
+    let injector = Injector::new();
+    let api: &Api = injector.get();
+
+    let before = api.limits.get();
+    assert_eq!(before.requests_per_second, 100);
+    assert_eq!(injector.reload_changed().unwrap(), Vec::<&str>::new());
+
+    // Pretend that an operator has just edited the limits
+    fs::write(limits_file(), "250").unwrap();
+    assert_eq!(
+        injector.reload_changed().unwrap(),
+        [std::any::type_name::<RateLimits>()]
+    );
+
+    // New snapshots see the change, but ones that were already taken are left alone
+    assert_eq!(api.limits.get().requests_per_second, 250);
+    assert_eq!(before.requests_per_second, 100);
+    let limits = api.limits.get();
+    println!(
+        "Allowing {} requests per second for {}",
+        limits.requests_per_second, limits.service.0
+    );
+
+    fs::remove_file(limits_file()).unwrap();
+}
+
+fn limits_file() -> PathBuf {
+    env::temp_dir().join(format!("injector_{}_reloadable_limits", std::process::id()))
+}
+
+fn read_limit() -> u32 {
+    fs::read_to_string(limits_file()).unwrap().parse().unwrap()
+}
+
+#[derive(Injectable)]
+struct ServiceName(#[inject(with = service_name)] &'static str);
+
+fn service_name() -> &'static str {
+    "the example api"
+}
+
+#[derive(Injectable)]
+#[reloadable(watch = limits_file())]
+struct RateLimits<'a> {
+    // Reloadable components can still depend on regular ones
+    service: &'a ServiceName,
+    #[inject(with = read_limit)]
+    requests_per_second: u32,
+}
+
+#[derive(Injectable)]
+struct Api<'a> {
+    limits: &'a Reloadable<'a, RateLimits<'a>>,
+}
@@ -0,0 +1,138 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+    time::Instant,
+};
+
+use injector::{BuildError, Injectable, Injector, RequestScope, constructor};
+
+fn main() {
+    let injector = Injector::new();
+    let database: &Database = injector.get();
+
+    {
+        let scope = injector
+            .request_scope()
+            .inject_value(RequestContext { path: "/orders".to_string(), user: Some("ana".into()) })
+            .build();
+
+        // Request scoped components are built when they are first needed, and only then
+        assert_eq!(scope.get::<Principal>().user, "ana");
+        assert_eq!(database.begun.get(), 0);
+        assert_eq!(scope.get::<OrdersHandler>().handle(), "ana listed orders in transaction 1");
+        assert_eq!(scope.get::<OrdersHandler>().handle(), "ana listed orders in transaction 1");
+        assert_eq!(&raw const *scope.get::<Database>(), &raw const *database);
+    }
+    assert_eq!(*database.finished.borrow(), ["rolled back 1"]);
+
+    // Leaving out a value that a request scoped component needs is an error, rather than a panic
+    let scope = injector.request_scope().build();
+    let error = scope.try_get::<Principal>().err().unwrap();
+    assert!(matches!(error, BuildError::MissingDependency { .. }));
+    println!("{}", error);
+
+    // Async code can reach the scope it is running in without having it passed down
+    let scope = injector
+        .request_scope()
+        .inject_value(RequestContext { path: "/health".to_string(), user: None })
+        .build();
+    assert_eq!(block_on(scope.in_scope(audit())), "anonymous called /health");
+    assert!(RequestScope::try_with_current(|_| ()).is_none());
+
+    let start = Instant::now();
+    for _ in 0..10_000 {
+        let scope = injector
+            .request_scope()
+            .inject_value(RequestContext { path: "/".to_string(), user: None })
+            .build();
+        scope.get::<Principal>();
+    }
+    println!("Served 10000 requests in {:?}", start.elapsed());
+}
+
+async fn audit() -> String {
+    RequestScope::with_current(|scope| {
+        let context: &RequestContext = scope.get();
+        format!("{} called {}", scope.get::<Principal>().user, context.path)
+    })
+}
+
+// A minimal executor, as nothing here ever waits
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[derive(Injectable)]
+struct Database {
+    #[inject(default)]
+    begun: Cell<u32>,
+    #[inject(default)]
+    finished: RefCell<Vec<String>>,
+}
+
+// Given to each scope, so marked as request scoped to stop singletons from depending on it
+#[derive(Injectable)]
+#[has_constructor]
+#[injectable(scope = request)]
+struct RequestContext {
+    path: String,
+    user: Option<String>,
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+#[injectable(scope = request)]
+struct Principal {
+    user: String,
+}
+
+#[constructor]
+fn authenticate(context: &RequestContext) -> Principal {
+    let user = context.user.clone().unwrap_or_else(|| "anonymous".to_string());
+    Principal { user }
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+#[injectable(scope = request)]
+struct Transaction<'a> {
+    database: &'a Database,
+    id: u32,
+}
+
+#[constructor]
+fn begin(database: &Database) -> Transaction<'_> {
+    database.begun.set(database.begun.get() + 1);
+    Transaction { database, id: database.begun.get() }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        let finished = format!("rolled back {}", self.id);
+        self.database.finished.borrow_mut().push(finished);
+    }
+}
+
+#[derive(Injectable)]
+#[injectable(scope = request)]
+struct OrdersHandler<'a> {
+    principal: &'a Principal,
+    transaction: &'a Transaction<'a>,
+}
+
+impl OrdersHandler<'_> {
+    fn handle(&self) -> String {
+        format!(
+            "{} listed orders in transaction {}",
+            self.principal.user, self.transaction.id
+        )
+    }
+}
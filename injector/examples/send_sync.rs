@@ -0,0 +1,76 @@
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    thread,
+};
+
+use injector::{Injectable, Injector, binding, multi_binding};
+
+fn main() {
+    let injector = Injector::new();
+    let workers: &Workers = injector.get();
+
+    // Every worker thread shares the same counter, borrowed straight from the injector
+    thread::scope(|scope| {
+        for job in &workers.jobs {
+            scope.spawn(|| job.run(workers.counter));
+        }
+    });
+    assert_eq!(workers.counter.current(), 11);
+
+    let counter = injector.get_trait_object::<dyn Counter + Send + Sync>();
+    println!("Counted to {}", counter.current());
+}
+
+trait Counter {
+    fn add(&self, amount: u32);
+    fn current(&self) -> u32;
+}
+
+#[derive(Injectable)]
+struct AtomicCounter {
+    #[inject(default)]
+    count: AtomicU32,
+}
+
+// The impl is for `dyn Counter`, but what gets bound is `dyn Counter + Send + Sync`
+#[binding(bounds = Send + Sync)]
+impl Counter for AtomicCounter {
+    fn add(&self, amount: u32) {
+        self.count.fetch_add(amount, Ordering::SeqCst);
+    }
+
+    fn current(&self) -> u32 {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+trait Job {
+    fn run(&self, counter: &dyn Counter);
+}
+
+#[derive(Injectable)]
+struct AddOne;
+
+#[multi_binding(bounds = Send + Sync)]
+impl Job for AddOne {
+    fn run(&self, counter: &dyn Counter) {
+        counter.add(1);
+    }
+}
+
+#[derive(Injectable)]
+#[binds(multi(dyn Job + Sync + Send))]
+struct AddTen;
+
+impl Job for AddTen {
+    fn run(&self, counter: &dyn Counter) {
+        counter.add(10);
+    }
+}
+
+#[derive(Injectable)]
+struct Workers<'a> {
+    counter: &'a (dyn Counter + Send + Sync),
+    #[from_multi_binding(dyn Job + Send + Sync)]
+    jobs: Vec<&'a (dyn Job + Send + Sync)>,
+}
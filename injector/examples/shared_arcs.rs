@@ -0,0 +1,61 @@
+use std::{sync::Arc, thread};
+
+use injector::{Injectable, Injector, binding};
+
+fn main() {
+    let injector = Injector::new();
+
+    // Arcs can be moved into threads, callbacks and anything else that needs 'static
+    let orders = injector.get_arc::<OrderService>();
+    let handle = thread::spawn(move || orders.place(3));
+    assert_eq!(handle.join().unwrap(), "placed 3 orders using postgres://localhost/shop");
+
+    let store = injector.get_trait_object_arc::<dyn Store + Send + Sync>();
+    let pool = injector.get_arc::<Pool>();
+    assert!(Arc::ptr_eq(&pool, &injector.get_arc::<OrderService>().pool));
+
+    // The Arcs hold their dependencies, so they keep working after the injector is gone
+    drop(injector);
+    assert_eq!(store.save("order"), "saved order to postgres://localhost/shop");
+    println!("{}", pool.url);
+}
+
+#[derive(Injectable)]
+struct Pool {
+    #[inject(with = database_url)]
+    url: String,
+}
+
+fn database_url() -> String {
+    "postgres://localhost/shop".to_string()
+}
+
+trait Store {
+    fn save(&self, item: &str) -> String;
+}
+
+// Types without a lifetime can hold their dependencies as Arcs instead of references
+#[derive(Injectable)]
+struct PostgresStore {
+    pool: Arc<Pool>,
+}
+
+#[binding(bounds = Send + Sync)]
+impl Store for PostgresStore {
+    fn save(&self, item: &str) -> String {
+        format!("saved {} to {}", item, self.pool.url)
+    }
+}
+
+#[derive(Injectable)]
+struct OrderService {
+    pool: Arc<Pool>,
+    store: Arc<dyn Store + Send + Sync>,
+}
+
+impl OrderService {
+    fn place(&self, count: u32) -> String {
+        let saved = self.store.save("order");
+        format!("placed {} orders using {}", count, saved.trim_start_matches("saved order to "))
+    }
+}
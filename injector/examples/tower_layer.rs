@@ -0,0 +1,123 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::pin,
+    sync::atomic::{AtomicU32, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use http::{HeaderMap, Request, Response, StatusCode};
+use injector::{
+    Injectable, Injector, constructor, global,
+    tower::{Inject, InjectorLayer},
+};
+use tower::{ServiceBuilder, ServiceExt, service_fn};
+
+fn main() {
+    // The layer makes its request scopes from the global injector
+    global::install(Injector::new()).unwrap();
+    let service = ServiceBuilder::new()
+        .layer(InjectorLayer::new())
+        .service_fn(list_orders);
+
+    // Calls go straight to the service, with no network involved
+    let request = Request::get("/orders").header("x-user", "ana").body(()).unwrap();
+    let response = block_on(service.clone().oneshot(request)).unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.body(), "ana can see 3 orders in transaction 1");
+
+    let response = block_on(service.clone().oneshot(Request::new(()))).unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // The request that was turned away never started a transaction
+    let request = Request::get("/orders").header("x-user", "bo").body(()).unwrap();
+    let response = block_on(service.oneshot(request)).unwrap();
+    assert_eq!(response.body(), "bo can see 3 orders in transaction 2");
+
+    // Without the layer, there is nothing to inject from
+    let unscoped = service_fn(list_orders);
+    let response_without_layer = block_on(unscoped.oneshot(Request::new(()))).unwrap();
+    assert_eq!(response_without_layer.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    println!("{}: {}", response.status(), response.body());
+}
+
+async fn list_orders(request: Request<()>) -> Result<Response<String>, Infallible> {
+    let principal = match Inject::<Principal>::from_request(&request) {
+        Ok(principal) => principal,
+        Err(error) => return Ok(respond(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())),
+    };
+    let Some(user) = &principal.user else {
+        return Ok(respond(StatusCode::UNAUTHORIZED, "who are you?".to_string()));
+    };
+
+    // Only requests that get this far start a transaction
+    let orders = Inject::<Orders<'static>>::from_request(&request).unwrap();
+    let orders = orders.get();
+    let body = format!(
+        "{} can see {} orders in transaction {}",
+        user, orders.database.orders, orders.transaction.id
+    );
+    Ok(respond(StatusCode::OK, body))
+}
+
+fn respond(status: StatusCode, body: String) -> Response<String> {
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response
+}
+
+// A minimal executor, as nothing here ever waits
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[derive(Injectable)]
+struct Database {
+    #[inject(with = seeded_orders)]
+    orders: u32,
+    #[inject(default)]
+    begun: AtomicU32,
+}
+
+fn seeded_orders() -> u32 {
+    3
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+#[injectable(scope = request)]
+struct Principal {
+    user: Option<String>,
+}
+
+// The layer gives each scope the request's headers
+#[constructor]
+fn authenticate(headers: &HeaderMap) -> Principal {
+    let user = headers.get("x-user").and_then(|user| user.to_str().ok());
+    Principal { user: user.map(String::from) }
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+#[injectable(scope = request)]
+struct Transaction {
+    id: u32,
+}
+
+#[constructor]
+fn begin(database: &Database) -> Transaction {
+    Transaction { id: database.begun.fetch_add(1, Ordering::Relaxed) + 1 }
+}
+
+#[derive(Injectable)]
+#[injectable(scope = request)]
+struct Orders<'a> {
+    database: &'a Database,
+    transaction: &'a Transaction,
+}
@@ -0,0 +1,71 @@
+use injector::{Injectable, Injector, binding, multi_binding};
+
+fn main() {
+    let injector = Injector::new();
+    let bus: &MessageBus = injector.get();
+
+    let placed = OrderPlaced { order: 7 };
+    let mut handled = bus
+        .order_placed
+        .iter()
+        .map(|handler| handler.handle(&placed))
+        .collect::<Vec<_>>();
+    handled.sort();
+    assert_eq!(handled, ["emailed about order 7", "reserved stock for order 7"]);
+
+    // The same type can implement the trait for several messages, and each is bound separately
+    let shipped = OrderShipped { order: 7, carrier: "the post" };
+    assert_eq!(bus.order_shipped.handle(&shipped), "emailed about order 7 going by the post");
+    assert_eq!(
+        injector.get_trait_object::<dyn Handler<OrderShipped>>().handle(&shipped),
+        "emailed about order 7 going by the post"
+    );
+    println!("{}", handled.join(", "));
+}
+
+trait Handler<M> {
+    fn handle(&self, message: &M) -> String;
+}
+
+struct OrderPlaced {
+    order: u32,
+}
+
+struct OrderShipped {
+    order: u32,
+    carrier: &'static str,
+}
+
+#[derive(Injectable)]
+struct Emails;
+
+#[multi_binding]
+impl Handler<OrderPlaced> for Emails {
+    fn handle(&self, message: &OrderPlaced) -> String {
+        format!("emailed about order {}", message.order)
+    }
+}
+
+#[binding]
+impl Handler<OrderShipped> for Emails {
+    fn handle(&self, message: &OrderShipped) -> String {
+        format!("emailed about order {} going by {}", message.order, message.carrier)
+    }
+}
+
+#[derive(Injectable)]
+struct Warehouse;
+
+#[multi_binding]
+impl Handler<OrderPlaced> for Warehouse {
+    fn handle(&self, message: &OrderPlaced) -> String {
+        format!("reserved stock for order {}", message.order)
+    }
+}
+
+#[derive(Injectable)]
+struct MessageBus<'a> {
+    #[from_multi_binding(dyn Handler<OrderPlaced>)]
+    order_placed: Vec<&'a dyn Handler<OrderPlaced>>,
+    order_shipped: &'a dyn Handler<OrderShipped>,
+}
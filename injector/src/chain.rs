@@ -0,0 +1,79 @@
+/// Every `#[multi_binding]` of a trait, in order, for building pipelines like middleware. Inject one
+/// with `#[from_chain(dyn Trait)] chain: Chain<'a, dyn Trait>`, and set the order of each element
+/// with `#[multi_binding(order = ...)]`, where lower orders run first.
+///
+/// The chain does not know how to call your trait, so it hands out [`Next`] handles instead. Give
+/// your trait a method that takes one, and have each element call [`Next::pop`] to carry on:
+///
+/// ```ignore
+/// trait Middleware {
+///     fn handle(&self, request: &str, next: Next<'_, dyn Middleware>) -> String;
+/// }
+///
+/// fn run(next: Next<'_, dyn Middleware>, request: &str) -> String {
+///     match next.pop() {
+///         Some((middleware, next)) => middleware.handle(request, next),
+///         None => format!("handled {}", request),
+///     }
+/// }
+/// ```
+pub struct Chain<'a, T: ?Sized> {
+    links: Vec<&'a T>,
+}
+
+/// The rest of a [`Chain`], from some element onwards.
+pub struct Next<'a, T: ?Sized> {
+    remaining: &'a [&'a T],
+}
+
+impl<'a, T: ?Sized> Chain<'a, T> {
+    pub(crate) fn new(links: Vec<&'a T>) -> Self {
+        Chain { links }
+    }
+
+    /// A handle to the whole chain, starting from the first element.
+    pub fn start(&self) -> Next<'_, T> {
+        Next {
+            remaining: &self.links,
+        }
+    }
+
+    /// Every element of the chain, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        self.links.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+}
+
+impl<'a, T: ?Sized> Next<'a, T> {
+    /// The next element of the chain, along with the handle to pass on to it, or `None` once every
+    /// element has run.
+    pub fn pop(self) -> Option<(&'a T, Next<'a, T>)> {
+        let (first, remaining) = self.remaining.split_first()?;
+        Some((*first, Next { remaining }))
+    }
+
+    /// How many elements are still to run.
+    pub fn len(&self) -> usize {
+        self.remaining.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+}
+
+impl<T: ?Sized> Clone for Next<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Next<'_, T> {}
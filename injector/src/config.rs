@@ -0,0 +1,336 @@
+//! Layered configuration, for components marked with `#[config(section = "...")]`.
+//!
+//! A [`Config`] is built up from a list of sources with [`ConfigLoader`], where each source
+//! overrides the ones before it. Typically that is a default file, then an environment specific
+//! file, then `PREFIX__SECTION__KEY` environment variables. Add the result to the injector with
+//! [`crate::InjectorBuilder::with_config`], and each `#[config(section = "...")]` component will be
+//! deserialised from its section of it.
+
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    path::PathBuf,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Configuration merged from every source given to a [`ConfigLoader`].
+pub struct Config {
+    root: Value,
+    // Kept so that the config can be reloaded from the same sources
+    loader: ConfigLoader,
+}
+
+/// Builds a [`Config`] out of layered sources. Later sources override earlier ones, key by key.
+#[derive(Clone)]
+pub struct ConfigLoader {
+    sources: Vec<Source>,
+    env_prefix: Option<String>,
+}
+
+#[derive(Clone)]
+enum Source {
+    File { path: PathBuf, required: bool },
+    Inline { format: Format, contents: String },
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Toml,
+    Json,
+}
+
+/// An error loading one of the sources for a [`Config`].
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A required file could not be read.
+    Io { path: PathBuf, error: io::Error },
+    /// A file was not a TOML or JSON file, judging by its extension.
+    UnsupportedFormat { path: PathBuf },
+    /// A source could not be parsed. The path is `None` for inline sources.
+    Parse {
+        path: Option<PathBuf>,
+        reason: String,
+    },
+}
+
+impl Config {
+    pub fn loader() -> ConfigLoader {
+        ConfigLoader {
+            sources: Vec::new(),
+            env_prefix: None,
+        }
+    }
+
+    /// Deserialise a section of the config, given as a dotted path like `"database"` or
+    /// `"database.pool"`. A missing section is treated as empty, so sections that can be entirely
+    /// defaulted don't need to appear in any file.
+    pub fn section<T: DeserializeOwned>(&self, section: &str) -> Result<T, serde_json::Error> {
+        let mut value = &self.root;
+        for key in section.split('.') {
+            match value.get(key) {
+                Some(inner) => value = inner,
+                None => return T::deserialize(Value::Object(Map::new())),
+            }
+        }
+
+        T::deserialize(value)
+    }
+
+    /// Load the config again from the same sources, to pick up any changes to them.
+    pub fn reload(&self) -> Result<Config, ConfigError> {
+        self.loader.clone().load()
+    }
+}
+
+impl ConfigLoader {
+    /// Add a TOML or JSON file (depending on its extension), which must exist.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.sources.push(Source::File {
+            path,
+            required: true,
+        });
+        self
+    }
+
+    /// Add a TOML or JSON file (depending on its extension), which is skipped if it does not exist.
+    /// This is useful for environment specific files, such as `config/{environment}.toml`.
+    pub fn optional_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.sources.push(Source::File {
+            path,
+            required: false,
+        });
+        self
+    }
+
+    /// Add TOML directly, for example defaults included with [`include_str!`].
+    pub fn toml(mut self, contents: impl Into<String>) -> Self {
+        self.sources.push(Source::Inline {
+            format: Format::Toml,
+            contents: contents.into(),
+        });
+        self
+    }
+
+    /// Add JSON directly, for example defaults included with [`include_str!`].
+    pub fn json(mut self, contents: impl Into<String>) -> Self {
+        self.sources.push(Source::Inline {
+            format: Format::Json,
+            contents: contents.into(),
+        });
+        self
+    }
+
+    /// Let environment variables such as `PREFIX__DATABASE__URL` override `database.url`. These are
+    /// applied after every other source. Values that look like numbers or booleans are treated as
+    /// such, and everything else is a string.
+    pub fn env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn load(self) -> Result<Config, ConfigError> {
+        let mut root = Value::Object(Map::new());
+        for source in self.sources.iter().cloned() {
+            if let Some(value) = source.load()? {
+                merge(&mut root, value);
+            }
+        }
+
+        if let Some(prefix) = &self.env_prefix {
+            let prefix = format!("{}__", prefix);
+            for (key, value) in env::vars() {
+                let Some(path) = key.strip_prefix(&prefix) else {
+                    continue;
+                };
+                let path = path.split("__").map(str::to_lowercase).collect();
+                merge(&mut root, nest(path, parse_env_value(value)));
+            }
+        }
+
+        Ok(Config { root, loader: self })
+    }
+}
+
+impl Source {
+    fn load(self) -> Result<Option<Value>, ConfigError> {
+        match self {
+            Source::File { path, required } => {
+                let format = match path.extension().and_then(|ext| ext.to_str()) {
+                    Some("toml") => Format::Toml,
+                    Some("json") => Format::Json,
+                    _ => return Err(ConfigError::UnsupportedFormat { path }),
+                };
+                let contents = match fs::read_to_string(&path) {
+                    Ok(contents) => contents,
+                    Err(error) if !required && error.kind() == io::ErrorKind::NotFound => {
+                        return Ok(None);
+                    }
+                    Err(error) => return Err(ConfigError::Io { path, error }),
+                };
+                format
+                    .parse(&contents)
+                    .map(Some)
+                    .map_err(|reason| ConfigError::Parse {
+                        path: Some(path),
+                        reason,
+                    })
+            }
+            Source::Inline { format, contents } => format
+                .parse(&contents)
+                .map(Some)
+                .map_err(|reason| ConfigError::Parse { path: None, reason }),
+        }
+    }
+}
+
+impl Format {
+    fn parse(self, contents: &str) -> Result<Value, String> {
+        match self {
+            Format::Toml => toml::from_str::<toml::Value>(contents)
+                .map_err(|error| error.to_string())
+                .and_then(|value| serde_json::to_value(value).map_err(|error| error.to_string())),
+            Format::Json => serde_json::from_str(contents).map_err(|error| error.to_string()),
+        }
+    }
+}
+
+/// Merge `overrides` into `base`. Tables are merged key by key, anything else is replaced.
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Wrap `value` in a table for each key in `path`, so `["a", "b"]` becomes `{ a: { b: value } }`.
+fn nest(path: Vec<String>, value: Value) -> Value {
+    path.into_iter().rev().fold(value, |value, key| {
+        let mut table = Map::new();
+        table.insert(key, value);
+        Value::Object(table)
+    })
+}
+
+fn parse_env_value(value: String) -> Value {
+    match serde_json::from_str::<Value>(&value) {
+        Ok(parsed @ (Value::Bool(_) | Value::Number(_))) => parsed,
+        _ => Value::String(value),
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, error } => {
+                write!(f, "unable to read {}: {}", path.display(), error)
+            }
+            ConfigError::UnsupportedFormat { path } => write!(
+                f,
+                "unable to read {}: config files must be .toml or .json",
+                path.display()
+            ),
+            ConfigError::Parse {
+                path: Some(path),
+                reason,
+            } => write!(f, "unable to parse {}: {}", path.display(), reason),
+            ConfigError::Parse { path: None, reason } => {
+                write!(f, "unable to parse inline config: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io { error, .. } => Some(error),
+            ConfigError::UnsupportedFormat { .. } | ConfigError::Parse { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Pool {
+        size: u32,
+        #[serde(default)]
+        timeout: Option<u32>,
+    }
+
+    #[test]
+    fn later_sources_override_earlier_ones_key_by_key() {
+        let config = Config::loader()
+            .toml("[database.pool]\nsize = 5\ntimeout = 30")
+            .json(r#"{ "database": { "pool": { "size": 10 } } }"#)
+            .load()
+            .unwrap();
+
+        let pool: Pool = config.section("database.pool").unwrap();
+        assert_eq!(pool, Pool { size: 10, timeout: Some(30) });
+    }
+
+    #[test]
+    fn missing_sections_are_empty() {
+        #[derive(Deserialize, Debug, PartialEq, Default)]
+        #[serde(default)]
+        struct Defaulted {
+            enabled: bool,
+        }
+
+        let config = Config::loader().load().unwrap();
+        assert_eq!(config.section::<Defaulted>("missing").unwrap(), Defaulted::default());
+        assert!(config.section::<Pool>("missing").is_err());
+    }
+
+    #[test]
+    fn reloading_reads_files_again() {
+        let path = env::temp_dir().join(format!(
+            "injector_{}_reloading_reads_files_again.json",
+            std::process::id()
+        ));
+        fs::write(&path, r#"{ "pool": { "size": 1 } }"#).unwrap();
+        let config = Config::loader().file(&path).load().unwrap();
+
+        fs::write(&path, r#"{ "pool": { "size": 2 } }"#).unwrap();
+        let reloaded = config.reload().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.section::<Pool>("pool").unwrap().size, 1);
+        assert_eq!(reloaded.section::<Pool>("pool").unwrap().size, 2);
+    }
+
+    #[test]
+    fn environment_values_are_typed_where_possible() {
+        assert_eq!(parse_env_value("8080".to_string()), Value::from(8080));
+        assert_eq!(parse_env_value("true".to_string()), Value::from(true));
+        assert_eq!(parse_env_value("localhost".to_string()), Value::from("localhost"));
+        assert_eq!(parse_env_value("[1, 2]".to_string()), Value::from("[1, 2]"));
+    }
+
+    #[test]
+    fn nested_values_merge_into_tables() {
+        let mut root = Value::Object(Map::new());
+        merge(&mut root, nest(vec!["a".into(), "b".into()], Value::from(1)));
+        merge(&mut root, nest(vec!["a".into(), "c".into()], Value::from(2)));
+        assert_eq!(root, serde_json::json!({ "a": { "b": 1, "c": 2 } }));
+    }
+}
@@ -2,11 +2,22 @@
 //! know about. These APIs are all public, so that the derive macro can implement them, but they
 //! should not be treated as visible **or stable**.
 
-use std::any::{Any, TypeId};
+use std::{
+    any::{Any, TypeId},
+    env,
+    fmt::Display,
+    marker::PhantomData,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
 
 pub use linkme;
 
-use crate::{Injectable, Injector};
+use crate::{
+    Chain, ComponentError, Injectable, Injector, Interceptor, Owned, Rejection, Reloadable,
+    runtime::Reload,
+};
 
 /// A companion trait to [`Injectable`]. If you implement `Injectable<'a>` for `YourType<'a>`, then
 /// you should implement `InjectableStatic` for `YourType<'static>`. Having a version of the type
@@ -21,6 +32,493 @@ pub trait InjectableStatic: Any {
     fn downcast(&self) -> &Self::Injectable<'_>;
 }
 
+/// Resolves a dependency of type `T` from the injector, whether `T` is [`Injectable`] (which
+/// includes the common standard library types) or a `'static` type from another crate that was
+/// registered with `#[provides]` or [`crate::InjectorBuilder::inject_foreign`]. Those can't be
+/// given an [`Injectable`] impl outside of their own crate, and the derive macro cannot tell the
+/// two apart, so it writes `(&Resolve::<T>::new()).resolve(injector)` and lets method resolution
+/// pick between [`ResolveInjectable`] (which matches first, on `Resolve<T>`) and
+/// [`ResolveForeign`] (which only matches after auto-ref, on `&Resolve<T>`).
+///
+/// The arguments of an [`InjectWith`] function go through here too, where they can also be trait
+/// objects. Those are picked up by [`ResolveTraitObject`], which only matches after both of the
+/// others have failed to, as it takes `Resolve<T>` by value.
+pub struct Resolve<T: ?Sized>(PhantomData<fn() -> *const T>);
+
+impl<T: ?Sized> Resolve<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Resolve(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Clone for Resolve<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Resolve<T> {}
+
+/// See [`Resolve`]. Fetches the dependency with [`Injector::get`].
+pub trait ResolveInjectable<'a, I> {
+    fn resolve(&self, injector: &'a Injector) -> &'a I;
+
+    /// The type ID that the dependency is stored under.
+    fn key(&self) -> Option<TypeId>;
+}
+
+impl<'a, I: Injectable<'a>> ResolveInjectable<'a, I> for Resolve<I> {
+    fn resolve(&self, injector: &'a Injector) -> &'a I {
+        injector.get()
+    }
+
+    fn key(&self) -> Option<TypeId> {
+        Some(TypeId::of::<I::Static>())
+    }
+}
+
+/// See [`Resolve`]. Fetches the dependency with [`Injector::get_foreign`].
+pub trait ResolveForeign<'a, T> {
+    fn resolve(&self, injector: &'a Injector) -> &'a T;
+
+    /// The type ID that the dependency is stored under.
+    fn key(&self) -> Option<TypeId>;
+}
+
+impl<'a, T: Any> ResolveForeign<'a, T> for &Resolve<T> {
+    fn resolve(&self, injector: &'a Injector) -> &'a T {
+        injector.get_foreign()
+    }
+
+    fn key(&self) -> Option<TypeId> {
+        Some(TypeId::of::<T>())
+    }
+}
+
+/// See [`Resolve`]. Fetches a `dyn Trait` dependency, like [`Injector::get_trait_object`] does.
+pub trait ResolveTraitObject<'a, T: ?Sized> {
+    fn resolve(self, injector: &'a Injector) -> &'a T;
+
+    /// The type ID that the dependency is stored under.
+    fn key(self) -> Option<TypeId>;
+}
+
+impl<'a, T: ?Sized + 'a> ResolveTraitObject<'a, T> for Resolve<T> {
+    fn resolve(self, injector: &'a Injector) -> &'a T {
+        injector.get_binding()
+    }
+
+    fn key(self) -> Option<TypeId> {
+        Some(typeid::of::<&T>())
+    }
+}
+
+/// Fills the argument slots that an [`InjectWith`] function doesn't have. It resolves to itself,
+/// without going near the injector.
+pub struct NoArgument;
+
+impl Resolve<NoArgument> {
+    pub fn resolve(&self, _injector: &Injector) -> &'static NoArgument {
+        &NoArgument
+    }
+
+    pub fn key(&self) -> Option<TypeId> {
+        None
+    }
+}
+
+/// Only ever implemented for nothing, so that the unsized types which aren't trait objects give a
+/// clear error when they are asked for, rather than being looked up as a binding.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be injected, as the injector only holds sized types and trait objects",
+    label = "this argument can't be supplied by the injector",
+    note = "take a reference to the owning type instead, such as `&String` or `&Vec<T>`"
+)]
+pub trait UnsizedArgument {}
+
+// These are inherent, so that they are picked before any of the `Resolve` traits.
+macro_rules! reject_unsized_argument {
+    ($(impl$(<$param:ident>)? for $ty:ty;)*) => {$(
+        impl$(<$param>)? Resolve<$ty> {
+            pub fn resolve<'a>(&self, _injector: &'a Injector) -> &'a $ty
+            where
+                &'a $ty: UnsizedArgument,
+            {
+                unreachable!("`UnsizedArgument` is never implemented")
+            }
+
+            pub fn key(&self) -> Option<TypeId> {
+                None
+            }
+        }
+    )*};
+}
+
+reject_unsized_argument! {
+    impl for str;
+    impl<T> for [T];
+    impl for std::path::Path;
+    impl for std::ffi::OsStr;
+}
+
+/// Shares a `#[binding]` as an `Arc<dyn Trait>` when its implementation is [`Owned`], in the same
+/// way that [`Resolve`] works. The derive macro writes `(&Share::<S>::new()).share(injector, f)`,
+/// where `f` unsizes the `Arc`, and [`ShareOwned`] (which matches first, on `Share<S>`) or
+/// [`ShareBorrowed`] (which only matches after auto-ref, on `&Share<S>`) picks what to do.
+pub struct Share<S>(PhantomData<fn() -> S>);
+
+impl<S> Share<S> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Share(PhantomData)
+    }
+}
+
+/// See [`Share`]. Shares the implementation with [`Injector::get_arc`].
+pub trait ShareOwned<S, T: ?Sized> {
+    fn share(&self, injector: &Injector, unsize: fn(Arc<S>) -> Arc<T>) -> Option<Arc<T>>;
+}
+
+impl<S: Owned, T: ?Sized> ShareOwned<S, T> for Share<S> {
+    fn share(&self, injector: &Injector, unsize: fn(Arc<S>) -> Arc<T>) -> Option<Arc<T>> {
+        Some(unsize(injector.get_arc()))
+    }
+}
+
+/// See [`Share`]. The implementation borrows from the injector, so it can't be shared.
+pub trait ShareBorrowed<S, T: ?Sized> {
+    fn share(&self, injector: &Injector, unsize: fn(Arc<S>) -> Arc<T>) -> Option<Arc<T>>;
+}
+
+impl<S, T: ?Sized> ShareBorrowed<S, T> for &Share<S> {
+    fn share(&self, _: &Injector, _: fn(Arc<S>) -> Arc<T>) -> Option<Arc<T>> {
+        None
+    }
+}
+
+/// Tells whether `S` is `Send`, for [`ScopeMeta::is_send`], in the same way that [`Resolve`] works.
+/// The derive macro writes `(&SendCheck::<S>::new()).is_send()`, and [`IsSend`] (which matches
+/// first, on `SendCheck<S>`) or [`IsNotSend`] (which only matches after auto-ref, on
+/// `&SendCheck<S>`) gives the answer.
+pub struct SendCheck<S: ?Sized>(PhantomData<fn() -> *const S>);
+
+impl<S: ?Sized> SendCheck<S> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        SendCheck(PhantomData)
+    }
+}
+
+/// See [`SendCheck`].
+pub trait IsSend {
+    fn is_send(&self) -> bool {
+        true
+    }
+}
+
+impl<S: ?Sized + Send> IsSend for SendCheck<S> {}
+
+/// See [`SendCheck`].
+pub trait IsNotSend {
+    fn is_send(&self) -> bool {
+        false
+    }
+}
+
+impl<S: ?Sized> IsNotSend for &SendCheck<S> {}
+
+/// The output of a `#[binding]` create function when the binding can also be shared, see
+/// [`bind`]. The injector stores both, so the binding is available from
+/// [`Injector::get_trait_object_arc`] as well.
+pub struct Shared {
+    pub(crate) trait_object: Box<dyn Any>,
+    pub(crate) arc: Box<dyn Any>,
+}
+
+/// Boxes up the result of a `#[binding]` create function, given the `&'static dyn Trait` and, if
+/// the implementation is [`Owned`], the `Arc<dyn Trait>` from [`Share`].
+pub fn bind<T: ?Sized + 'static>(trait_object: &'static T, shared: Option<Arc<T>>) -> Box<dyn Any> {
+    match shared {
+        Some(arc) => Box::new(Shared {
+            trait_object: Box::new(trait_object),
+            arc: Box::new(arc),
+        }),
+        None => Box::new(trait_object),
+    }
+}
+
+/// A function that can initialise a field marked with `#[inject(with = some_fn)]`. The arguments of
+/// the function must all be references to things the injector holds: [`Injectable`] types, foreign
+/// types and `dyn Trait`s alike.
+///
+/// The derive macro only has the name of the function, so it doesn't know how many arguments there
+/// are, or what their types are. Instead it asks for [`Self::arguments`], which always has six
+/// [`Resolve`]rs (padded out with [`NoArgument`]). Each of those has a concrete type once the
+/// function is known, so they are resolved in the same way as fields are, and passed back to
+/// [`Self::call`].
+pub trait InjectWith<'a, Args, Output> {
+    /// A [`Resolve`] for each argument, followed by `Resolve<NoArgument>`s up to six.
+    type Arguments;
+
+    /// What each of [`Self::Arguments`] resolves to.
+    type Resolved;
+
+    fn arguments(&self) -> Self::Arguments;
+
+    fn call(&self, arguments: Self::Resolved) -> Output;
+}
+
+macro_rules! no_argument {
+    ($unused:ident) => {
+        NoArgument
+    };
+}
+
+macro_rules! impl_inject_with {
+    ($($arg:ident),*; $($unused:ident),*) => {
+        impl<'a, Function, Output, $($arg),*> InjectWith<'a, ($(&'a $arg,)*), Output> for Function
+        where
+            Function: Fn($(&'a $arg),*) -> Output,
+            $($arg: ?Sized + 'a,)*
+        {
+            type Arguments = ($(Resolve<$arg>,)* $(Resolve<no_argument!($unused)>,)*);
+            type Resolved = ($(&'a $arg,)* $(&'a no_argument!($unused),)*);
+
+            fn arguments(&self) -> Self::Arguments {
+                ($(Resolve::<$arg>::new(),)* $(Resolve::<no_argument!($unused)>::new(),)*)
+            }
+
+            #[allow(non_snake_case)] // the arguments are named after their types
+            fn call(&self, ($($arg,)* $($unused,)*): Self::Resolved) -> Output {
+                let _ = ($($unused,)*);
+                self($($arg),*)
+            }
+        }
+    };
+}
+
+impl_inject_with!(; A, B, C, D, E, F);
+impl_inject_with!(A; B, C, D, E, F);
+impl_inject_with!(A, B; C, D, E, F);
+impl_inject_with!(A, B, C; D, E, F);
+impl_inject_with!(A, B, C, D; E, F);
+impl_inject_with!(A, B, C, D, E; F);
+impl_inject_with!(A, B, C, D, E, F;);
+
+/// Reads a field marked with `#[inject(env = "...")]` from the environment, falling back to parsing
+/// the default (if there is one) when the variable is not set.
+pub fn from_env<T>(variable: &'static str, default: Option<&'static str>) -> Result<T, ComponentError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = match (env::var(variable), default) {
+        (Ok(value), _) => value,
+        (Err(_), Some(default)) => default.to_string(),
+        (Err(_), None) => return Err(ComponentError::MissingEnvironmentVariable { variable }),
+    };
+
+    value
+        .parse()
+        .map_err(|error: T::Err| ComponentError::InvalidEnvironmentVariable {
+            variable,
+            reason: error.to_string(),
+            value,
+        })
+}
+
+/// Wraps everything generated for a component marked with `#[config(section = "...")]`, so that
+/// leaving out the `config` feature gives one clear error rather than a pile of unresolved paths.
+#[cfg(feature = "config")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_config_feature {
+    ($($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+#[cfg(not(feature = "config"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_config_feature {
+    ($($tokens:tt)*) => {
+        ::std::compile_error!("#[config] needs the `config` feature of injector to be enabled");
+    };
+}
+
+pub use crate::__requires_config_feature as requires_config_feature;
+
+/// Deserialises a component marked with `#[config(section = "...")]` from the config that was
+/// added to the injector.
+#[cfg(feature = "config")]
+pub fn from_config<T>(injector: &Injector, section: &'static str) -> Result<T, ComponentError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let config = injector
+        .try_get_foreign::<crate::config::Config>()
+        .ok_or(ComponentError::MissingConfig)?;
+
+    config
+        .section(section)
+        .map_err(|error| ComponentError::InvalidConfig {
+            section,
+            reason: error.to_string(),
+        })
+}
+
+/// Like [`from_config`], but loads the config again from its sources first. This is used for
+/// components that are also marked with `#[reloadable(watch = ...)]`, so that rebuilding them picks
+/// up the change that triggered it.
+#[cfg(feature = "config")]
+pub fn from_reloaded_config<T>(injector: &Injector, section: &'static str) -> Result<T, ComponentError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let config = injector
+        .try_get_foreign::<crate::config::Config>()
+        .ok_or(ComponentError::MissingConfig)?
+        .reload()
+        .map_err(|error| ComponentError::InvalidConfig {
+            section,
+            reason: error.to_string(),
+        })?;
+
+    config
+        .section(section)
+        .map_err(|error| ComponentError::InvalidConfig {
+            section,
+            reason: error.to_string(),
+        })
+}
+
+/// Runs the function given in `#[config(section = "...", validate = some_fn)]`.
+#[cfg(feature = "config")]
+pub fn validate_config<T, E>(
+    section: &'static str,
+    config: &T,
+    validate: impl FnOnce(&T) -> Result<(), E>,
+) -> Result<(), ComponentError>
+where
+    E: Display,
+{
+    validate(config).map_err(|error| ComponentError::InvalidConfig {
+        section,
+        reason: error.to_string(),
+    })
+}
+
+/// Collects every multi binding of `T` for a field marked with `#[from_chain(dyn Trait)]`. Unlike
+/// [`Injector::get_all_trait_objects`], having none is fine.
+pub fn chain<T: ?Sized + 'static>(injector: &Injector) -> Chain<'_, T> {
+    let links = injector
+        .try_get_all_trait_objects()
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    Chain::new(links)
+}
+
+/// Every registered [`Interceptor`], for the proxy generated by `#[binding(intercept)]`. Unlike
+/// [`Injector::get_all_trait_objects`], having none is fine.
+///
+/// # Safety
+/// The returned trait objects are not really `'static`, see the docs for [`BindingMeta::create`].
+pub unsafe fn interceptors(injector: &Injector) -> Vec<&'static dyn Interceptor> {
+    let Some(interceptors) = injector.try_get_all_trait_objects::<dyn Interceptor>() else {
+        return Vec::new();
+    };
+
+    interceptors
+        .map(|interceptor| {
+            // SAFETY: The caller promises to treat these as borrowed from the injector
+            unsafe { std::mem::transmute::<&dyn Interceptor, &'static dyn Interceptor>(interceptor) }
+        })
+        .collect()
+}
+
+/// Turns a [`Rejection`] from an [`Interceptor`] into what the intercepted method returns. The
+/// derive macro writes `(&Reject::<Output>::new()).reject(rejection)`, which picks
+/// [`RejectWithError`] for methods that return a `Result` with a compatible error, and otherwise
+/// falls back to [`RejectByPanicking`] after auto-ref, like [`Resolve`] does.
+pub struct Reject<T>(PhantomData<fn() -> T>);
+
+impl<T> Reject<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Reject(PhantomData)
+    }
+}
+
+/// See [`Reject`]. Returns the rejection as the error.
+pub trait RejectWithError<T> {
+    fn reject(&self, rejection: Rejection) -> T;
+}
+
+impl<T, E: From<Rejection>> RejectWithError<Result<T, E>> for Reject<Result<T, E>> {
+    fn reject(&self, rejection: Rejection) -> Result<T, E> {
+        Err(rejection.into())
+    }
+}
+
+/// See [`Reject`]. There is nothing to return, so this panics with the rejection.
+pub trait RejectByPanicking<T> {
+    fn reject(&self, rejection: Rejection) -> T;
+}
+
+impl<T> RejectByPanicking<T> for &Reject<T> {
+    fn reject(&self, rejection: Rejection) -> T {
+        panic!("{}", rejection)
+    }
+}
+
+/// Implemented for `dyn Trait` by `#[interceptable]`, with the names of every method of the trait,
+/// so that `#[binding(intercept)]` can check that none of them are left to their default.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not marked with #[interceptable]",
+    note = "#[binding(intercept)] needs the trait to be marked with #[interceptable]"
+)]
+pub trait Interceptable {
+    const METHODS: &'static [&'static str];
+}
+
+/// Checks that an intercepted impl writes out every method of its trait, as the proxy can only
+/// forward the methods it knows about. This runs at compile time.
+pub const fn check_intercepted_methods(trait_methods: &[&str], impl_methods: &[&str]) {
+    let mut i = 0;
+    while i < trait_methods.len() {
+        let mut found = false;
+        let mut j = 0;
+        while j < impl_methods.len() {
+            found |= const_str_eq(trait_methods[i], impl_methods[j]);
+            j += 1;
+        }
+        if !found {
+            panic!(
+                "#[binding(intercept)] impls must write out every method of the trait, as default \
+                methods can't be intercepted"
+            );
+        }
+        i += 1;
+    }
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 /// Runtime metadata about a type that the injector needs.
 pub struct InjectMeta {
     /// The type ID of the [`InjectableStatic`] version of the type we are injecting.
@@ -29,9 +527,18 @@ pub struct InjectMeta {
     /// The name of the type we are injecting.
     pub name: &'static str,
 
+    /// For constructors that return a tuple, `this` is the type ID of the first element, and these
+    /// are the type IDs of the rest of them, in order. Each element becomes a separate component.
+    pub also_creates: Vec<TypeId>,
+
     // The type IDs of the [`InjectableStatic`] versions of the types we require for construction.
     pub dependencies: Vec<TypeId>,
 
+    /// The dependencies that are on every multi binding of a trait, like `Vec<&dyn Trait>`. Those
+    /// are just empty when nothing implements the trait, rather than missing. Each of these is also
+    /// in [`Self::dependencies`].
+    pub collections: Vec<TypeId>,
+
     /// A function which creates our type. The injector is provided so that we are able to call
     /// [`Injector::get`] within this method. The injector runtime will ensure that dependencies are
     /// created before their dependents.
@@ -47,16 +554,43 @@ pub struct InjectMeta {
     /// To call this function safely, we ensure:
     /// - The returned value of this function is stored inside the `Injector`, in a private field
     /// - Any time we use this value, we first call [`InjectableStatic::downcast`] and restore the
-    ///     lifetime parameter to ensure that it does not outlive the injector that it borrowed from
-    ///     to create it.
+    ///   lifetime parameter to ensure that it does not outlive the injector that it borrowed from
+    ///   to create it.
     /// - When dropping the `Injector`, we drop fields in the reverse order that they were created
-    ///     in, so any references stored inside this value (which are to fields that were inside the
-    ///     injector when this value was created) are still valid when [`std::ops::Drop::drop`] is
-    ///     called.
-    pub create: unsafe fn(&Injector) -> Box<dyn Any>,
+    ///   in, so any references stored inside this value (which are to fields that were inside the
+    ///   injector when this value was created) are still valid when [`std::ops::Drop::drop`] is
+    ///   called.
+    ///
+    /// If [`Self::also_creates`] is not empty, this must return a [`Components`] with one item per
+    /// created type, starting with `this`.
+    pub create: unsafe fn(&Injector) -> Result<Box<dyn Any>, ComponentError>,
 
     /// For trait objects only: this indicates that this is not the only instance of the given type.
     pub is_multi_binding: bool,
+
+    /// For multi bindings only: where this sits among the other bindings of the same trait, see
+    /// [`BindingMeta::order`].
+    pub order: i32,
+}
+
+/// The output of an [`InjectMeta::create`] function that creates several components at once. The
+/// injector stores each of these separately, in order.
+pub struct Components(pub Vec<Box<dyn Any>>);
+
+/// The output of the create function for a `#[reloadable(watch = ...)]` type, so that the injector
+/// knows to check it in [`Injector::reload_changed`]. It gets stored as the [`Reloadable`] inside.
+pub struct Reloading(pub(crate) Box<dyn Reload>);
+
+/// Creates the [`Reloadable`] wrapper for a type marked with `#[reloadable(watch = ...)]`, given
+/// the regular create function for that type. That function is called once now, and again each
+/// time the watched file changes.
+pub fn create_reloadable<S: InjectableStatic>(
+    injector: &Injector,
+    watch: impl Into<PathBuf>,
+    create: unsafe fn(&Injector) -> Result<Box<dyn Any>, ComponentError>,
+) -> Result<Box<dyn Any>, ComponentError> {
+    let reloadable = Reloadable::<S>::new(injector, watch.into(), create)?;
+    Ok(Box::new(Reloading(Box::new(reloadable))))
 }
 
 /// Runtime metadata about dyn trait bindings that the injector needs.
@@ -74,6 +608,26 @@ pub struct BindingMeta {
     /// Is this a "multi binding"?
     pub is_multi_binding: bool,
 
+    /// The order given with `#[multi_binding(order = ...)]`, or 0. Multi bindings with a lower
+    /// order come first in [`Injector::get_all_trait_objects`] and [`crate::Chain`], and ones with
+    /// the same order come in the order they were created.
+    pub order: i32,
+
+    /// Was this declared with `#[default_binding]`? These are dropped if the trait has any other
+    /// binding.
+    pub is_default: bool,
+
+    /// Set for `#[decorates(dyn Trait)]` implementations, which wrap the regular binding instead of
+    /// competing with it.
+    pub decorates: Option<Decoration>,
+
+    /// The profile given with `#[binding(profile = "...")]`, if any. See
+    /// [`crate::InjectorBuilder::profile`].
+    pub profile: Option<&'static str>,
+
+    /// The key given with `#[binding(key = "...")]`, if any. See [`crate::InjectorBuilder::select`].
+    pub key: Option<&'static str>,
+
     /// See [`InjectMeta::create`], this should create a `Box<&'static dyn Foo>` (which then gets
     /// cast to `Box<dyn Any>`). To implement this function:
     /// 1. Use the injector to get an instance of the concrete type that implements your trait
@@ -84,9 +638,36 @@ pub struct BindingMeta {
     /// # Safety
     /// See the safety docs for [`InjectMeta::create`], the same rules apply here. The transmute in
     /// step 2 is how we implement the upcast referenced there, to make trait objects work.
-    pub create: unsafe fn(&Injector) -> Box<dyn Any>,
+    pub create: unsafe fn(&Injector) -> Result<Box<dyn Any>, ComponentError>,
+}
+
+/// Marks a type as `#[injectable(scope = request)]`, see `REQUEST_SCOPED_REGISTRY`.
+pub struct ScopeMeta {
+    /// The type ID of the request scoped type.
+    pub this: TypeId,
+
+    /// The name of the type, for error messages.
+    pub name: &'static str,
+
+    /// Whether the type is `Send`, which the tower layer needs of every request scoped type, as it
+    /// drops scopes on whichever thread finishes with them. See [`SendCheck`].
+    pub is_send: bool,
+}
+
+/// Where a `#[decorates(dyn Trait)]` implementation sits in the stack of decorators for its trait.
+#[derive(Clone, Copy)]
+pub struct Decoration {
+    /// The type ID of [`DecoratorInput`] for the concrete type. The injector uses this to stand in
+    /// for the layer beneath this decorator, when working out what order to create things in.
+    pub input: TypeId,
+
+    /// Decorators with a lower order are closer to the decorated binding.
+    pub order: i32,
 }
 
+/// Only used for its [`TypeId`], see [`Decoration::input`].
+pub struct DecoratorInput<T: ?Sized>(PhantomData<T>);
+
 /// Runtime metadata for all the types that we want to inject, aggregated into one spot by the
 /// linker. For more info, see the [`linkme`] crate.
 #[linkme::distributed_slice]
@@ -96,3 +677,8 @@ pub static INJECTION_REGISTRY: [fn() -> InjectMeta];
 /// spot by the linker. For more info, see the [`linkme`] crate.
 #[linkme::distributed_slice]
 pub static BINDING_REGISTRY: [fn() -> BindingMeta];
+
+/// The types marked `#[injectable(scope = request)]`. Their [`InjectMeta`]s are still in the
+/// `INJECTION_REGISTRY`, but they are only built by a [`crate::RequestScope`].
+#[linkme::distributed_slice]
+pub static REQUEST_SCOPED_REGISTRY: [fn() -> ScopeMeta];
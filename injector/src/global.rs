@@ -0,0 +1,100 @@
+//! A process-wide [`Injector`], for code that can't be handed a reference to one, like signal
+//! handlers, `log` backends and FFI callbacks. Call [`install`] once at startup, and then use
+//! [`get`], [`get_trait_object`] or the [`crate::inject!`] macro from anywhere.
+//!
+//! The installed injector lives until the process exits, so everything it hands out is `'static`.
+//! It can be reached from any thread, so only components that are `Sync` can be fetched from it.
+
+use std::{error::Error, fmt, sync::OnceLock};
+
+use crate::{Injectable, Injector};
+
+static GLOBAL: OnceLock<GlobalInjector> = OnceLock::new();
+
+struct GlobalInjector(Injector);
+
+// SAFETY: The injector is never changed once it is installed, and never dropped. Every component
+// that can be fetched from it is `Sync`, which makes sharing it between threads fine, so the only
+// thing left is the lookups themselves, which are reads of immutable maps.
+unsafe impl Send for GlobalInjector {}
+unsafe impl Sync for GlobalInjector {}
+
+/// An error using the global injector.
+#[derive(Debug)]
+pub enum GlobalError {
+    /// [`install`] was called when an injector was already installed.
+    AlreadyInstalled,
+    /// The global injector was used before [`install`] was called.
+    NotInstalled,
+}
+
+/// Install the global injector. This can only happen once per process.
+pub fn install(injector: Injector) -> Result<(), GlobalError> {
+    GLOBAL
+        .set(GlobalInjector(injector))
+        .map_err(|_| GlobalError::AlreadyInstalled)
+}
+
+/// Whether [`install`] has been called yet.
+pub fn is_installed() -> bool {
+    GLOBAL.get().is_some()
+}
+
+/// Fetch an item from the global injector, see [`Injector::get`]. This will panic if the injector
+/// has not been installed, use [`try_get`] to handle that instead.
+pub fn get<T: Injectable<'static> + Sync>() -> &'static T {
+    try_get().unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Like [`get`], but returns an error if the injector has not been installed.
+pub fn try_get<T: Injectable<'static> + Sync>() -> Result<&'static T, GlobalError> {
+    Ok(installed()?.get())
+}
+
+/// Fetch a trait object from the global injector, see [`Injector::get_trait_object`]. Bind the
+/// trait with `#[binding(bounds = Sync)]` (or `Send + Sync`) to be able to fetch it here. This will
+/// panic if the injector has not been installed, use [`try_get_trait_object`] to handle that
+/// instead.
+pub fn get_trait_object<T: ?Sized + Sync + 'static>() -> &'static T {
+    try_get_trait_object().unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Like [`get_trait_object`], but returns an error if the injector has not been installed.
+pub fn try_get_trait_object<T: ?Sized + Sync + 'static>() -> Result<&'static T, GlobalError> {
+    Ok(installed()?.get_trait_object())
+}
+
+pub(crate) fn installed() -> Result<&'static Injector, GlobalError> {
+    GLOBAL
+        .get()
+        .map(|global| &global.0)
+        .ok_or(GlobalError::NotInstalled)
+}
+
+/// Fetch a component from the [`global`](crate::global) injector, like `inject!(Database)` or
+/// `inject!(dyn Mailer + Sync)`. This panics if the injector has not been installed.
+#[macro_export]
+macro_rules! inject {
+    (dyn $($bounds:tt)+) => {
+        $crate::global::get_trait_object::<dyn $($bounds)+>()
+    };
+    ($type:ty) => {
+        $crate::global::get::<$type>()
+    };
+}
+
+impl fmt::Display for GlobalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlobalError::AlreadyInstalled => {
+                write!(f, "the global injector has already been installed")
+            }
+            GlobalError::NotInstalled => {
+                write!(f, "the global injector has not been installed, see injector::global::install")
+            }
+        }
+    }
+}
+
+impl Error for GlobalError {}
+
@@ -0,0 +1,65 @@
+use std::{error::Error, fmt, time::Duration};
+
+/// Runs around every method call on a binding marked with `#[binding(intercept)]`. Register one
+/// with `#[multi_binding] impl Interceptor for YourType`, and it will see the calls to every
+/// intercepted binding in the injector. The trait of an intercepted binding must be marked with
+/// `#[interceptable]`, and its impl must write out every method, as the proxy can't forward the
+/// ones left to their default.
+///
+/// Interceptors are called in their `#[multi_binding(order = ...)]` order before the call, lowest
+/// first, and in the reverse order after it, so each one wraps the ones with a higher order.
+/// Interceptors with the same order are called in an unspecified order. Both methods do nothing by
+/// default.
+///
+/// An interceptor can stop the call (to check authorisation, say) by returning a [`Rejection`]
+/// from [`Self::before`]. The method is then never called, and neither is `before` on the
+/// interceptors after it, nor `after` on any of them. A method that returns `Result<T, E>` where
+/// `E: From<Rejection>` returns the rejection as its error, and any other method panics with it.
+pub trait Interceptor {
+    #[allow(unused_variables)]
+    fn before(&self, call: &MethodCall) -> Result<(), Rejection> {
+        Ok(())
+    }
+
+    #[allow(unused_variables)]
+    fn after(&self, call: &MethodCall, elapsed: Duration) {}
+}
+
+/// The method of an intercepted binding that is being called.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MethodCall {
+    /// The name of the trait, like `dyn my_crate::Repository`.
+    pub trait_object: &'static str,
+    /// The name of the method, like `find`.
+    pub method: &'static str,
+}
+
+/// An [`Interceptor`] stopping a call before it reached the binding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rejection {
+    /// The call that was stopped.
+    pub call: MethodCall,
+    /// Why it was stopped.
+    pub reason: String,
+}
+
+impl Rejection {
+    pub fn new(call: &MethodCall, reason: impl Into<String>) -> Self {
+        Rejection {
+            call: *call,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Call to {} on {} was rejected: {}",
+            self.call.method, self.call.trait_object, self.reason
+        )
+    }
+}
+
+impl Error for Rejection {}
@@ -1,9 +1,28 @@
+mod chain;
+#[cfg(feature = "config")]
+pub mod config;
 #[doc(hidden)]
 pub mod derive_api;
+pub mod global;
+mod intercept;
 mod runtime;
+#[cfg(feature = "tower")]
+pub mod tower;
 
-pub use injector_derive::{Injectable, binding, constructor, multi_binding};
-pub use runtime::{InjectorBuilder, Injector};
+// The derive macros refer to `::injector`, this lets unit tests use them too
+#[cfg(test)]
+extern crate self as injector;
+
+pub use injector_derive::{
+    Injectable, binding, constructor, decorates, default_binding, interceptable, multi_binding,
+    provides,
+};
+pub use chain::{Chain, Next};
+pub use intercept::{Interceptor, MethodCall, Rejection};
+pub use runtime::{
+    BuildError, ChildInjector, ChildInjectorBuilder, ComponentError, InRequestScope,
+    InjectorBuilder, Injector, Reloadable, RequestScope, RequestScopeBuilder, Snapshot,
+};
 
 /// A type that the [`Injector`] can manage. This type should have a set of dependencies (which are
 /// also [`Injectable`]), and a way to construct the type from those dependencies. Use the
@@ -31,3 +50,71 @@ where
     #[doc(hidden)]
     unsafe fn upcast(self) -> Self::Static;
 }
+
+/// An [`Injectable`] type that does not borrow anything from the injector, which is every type
+/// without a lifetime parameter. These can be shared beyond the injector with
+/// [`Injector::get_arc`], and bindings for them with [`Injector::get_trait_object_arc`].
+pub trait Owned: for<'a> Injectable<'a, Static = Self> {}
+
+impl<T: for<'a> Injectable<'a, Static = T>> Owned for T {}
+
+// Foreign types from the standard library, which are often injected as they are with
+// `#[provides]` or `InjectorBuilder::inject_foreign`. These don't borrow anything, so they can be
+// fetched with `Injector::get` like any other owned component. Types from other crates can't be
+// given an impl outside of their own crate, so those still go through `Injector::get_foreign`.
+macro_rules! impl_injectable_for_foreign {
+    ($(impl$(<$($param:ident $(: ?$unsized:ident)?),*>)? for $ty:ty;)*) => {$(
+        impl<'a, $($($param: $(?$unsized +)? std::any::Any),*)?> Injectable<'a> for $ty {
+            type Static = Self;
+
+            unsafe fn upcast(self) -> Self::Static {
+                self
+            }
+        }
+
+        impl$(<$($param: $(?$unsized +)? std::any::Any),*>)? derive_api::InjectableStatic for $ty {
+            type Injectable<'a> = Self;
+
+            fn downcast(&self) -> &Self::Injectable<'_> {
+                self
+            }
+        }
+    )*};
+}
+
+impl_injectable_for_foreign! {
+    impl for bool;
+    impl for char;
+    impl for u8;
+    impl for u16;
+    impl for u32;
+    impl for u64;
+    impl for u128;
+    impl for usize;
+    impl for i8;
+    impl for i16;
+    impl for i32;
+    impl for i64;
+    impl for i128;
+    impl for isize;
+    impl for f32;
+    impl for f64;
+    impl for String;
+    impl for std::ffi::OsString;
+    impl for std::path::PathBuf;
+    impl for std::time::Duration;
+    impl for std::time::Instant;
+    impl for std::time::SystemTime;
+    impl for std::net::IpAddr;
+    impl for std::net::Ipv4Addr;
+    impl for std::net::Ipv6Addr;
+    impl for std::net::SocketAddr;
+    impl<T: ?Sized> for Box<T>;
+    impl<T> for Vec<T>;
+    impl<T> for Option<T>;
+    impl<T> for std::collections::VecDeque<T>;
+    impl<T> for std::collections::BTreeSet<T>;
+    impl<K, V> for std::collections::BTreeMap<K, V>;
+    impl<T, S> for std::collections::HashSet<T, S>;
+    impl<K, V, S> for std::collections::HashMap<K, V, S>;
+}
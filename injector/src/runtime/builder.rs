@@ -1,11 +1,17 @@
-use std::any::TypeId;
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+};
 
 use multimap::MultiMap;
 
-use super::Injector;
+use super::{BuildError, Injector};
 use crate::{
     Injectable,
-    derive_api::{BINDING_REGISTRY, INJECTION_REGISTRY, InjectMeta, InjectableStatic},
+    derive_api::{
+        BINDING_REGISTRY, BindingMeta, INJECTION_REGISTRY, InjectMeta, InjectableStatic,
+        REQUEST_SCOPED_REGISTRY,
+    },
 };
 
 /// A builder for [`Injector`]. This struct lets you add values manually via [`Self::inject_value`]
@@ -13,11 +19,66 @@ use crate::{
 /// values manually, call [`Self::build_the_world`].
 pub struct InjectorBuilder {
     injector: Injector,
+    profile: Option<String>,
+    // Keyed by the type ID of `&'static dyn Trait`, like `BindingMeta::trait_object`
+    selections: HashMap<TypeId, Selection>,
+}
+
+struct Selection {
+    trait_object: &'static str,
+    key: SelectionKey,
+}
+
+enum SelectionKey {
+    Given(String),
+    #[cfg(feature = "config")]
+    FromConfig(&'static str),
 }
 
 impl InjectorBuilder {
     pub(crate) fn new(injector: Injector) -> Self {
-        InjectorBuilder { injector }
+        InjectorBuilder {
+            injector,
+            profile: None,
+            selections: HashMap::new(),
+        }
+    }
+
+    /// Use the bindings marked with `#[binding(profile = "...")]` for this profile. For each trait,
+    /// if none of its bindings are for this profile, the ones without a profile are used instead.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Use the `#[binding(key = "...")]` implementation of `T` with this key, where `T` is a trait
+    /// object type like `dyn Storage`. The other implementations of `T` are not constructed, and
+    /// neither is anything that only they depend on.
+    ///
+    /// Without a selection, the implementation of `T` that has no key is used, if there is one.
+    pub fn select<T: ?Sized + 'static>(mut self, key: impl Into<String>) -> Self {
+        self.selections.insert(
+            TypeId::of::<&'static T>(),
+            Selection {
+                trait_object: std::any::type_name::<T>(),
+                key: SelectionKey::Given(key.into()),
+            },
+        );
+        self
+    }
+
+    /// Like [`Self::select`], but reads the key from the config added with [`Self::with_config`],
+    /// at a dotted path like `"storage.backend"`.
+    #[cfg(feature = "config")]
+    pub fn select_from_config<T: ?Sized + 'static>(mut self, path: &'static str) -> Self {
+        self.selections.insert(
+            TypeId::of::<&'static T>(),
+            Selection {
+                trait_object: std::any::type_name::<T>(),
+                key: SelectionKey::FromConfig(path),
+            },
+        );
+        self
     }
 
     pub fn inject_value<I>(mut self, value: I) -> Self
@@ -28,8 +89,75 @@ impl InjectorBuilder {
         self
     }
 
+    /// Add a value of a type that does not derive [`Injectable`], typically because it comes
+    /// from another crate. Other components can then depend on `&T` as usual. Standard library
+    /// types can then be fetched with [`Injector::get`], and others with
+    /// [`Injector::get_foreign`].
+    pub fn inject_foreign<T>(mut self, value: T) -> Self
+    where
+        T: Any,
+    {
+        self.injector.store(value);
+        self
+    }
+
+    /// Add layered configuration, which every `#[config(section = "...")]` component is
+    /// deserialised from. See [`crate::config`] for how to load it.
+    #[cfg(feature = "config")]
+    pub fn with_config(self, config: crate::config::Config) -> Self {
+        self.inject_foreign(config)
+    }
+
+    /// Parse the command line arguments into `C`, and add it like [`Self::inject_value`] so that
+    /// constructors can take `&C`. `C` can be a `#[derive(clap::Parser, Injectable)]` struct marked
+    /// with `#[has_constructor]`, or just a `#[derive(clap::Parser)]` one.
+    ///
+    /// As with [`clap::Parser::parse`], this prints the help or the parse error and exits the
+    /// process when the arguments ask for `--help` or can't be parsed. This happens here, before any
+    /// constructor has run.
+    #[cfg(feature = "cli")]
+    pub fn with_args<C>(mut self) -> Self
+    where
+        C: clap::Parser + Any,
+    {
+        self.injector.store(C::parse());
+        self
+    }
+
+    /// Like [`Self::with_args`], but parses the given arguments rather than the ones the process was
+    /// started with, and returns an error rather than exiting. The first argument is the binary
+    /// name.
+    #[cfg(feature = "cli")]
+    pub fn try_with_args_from<C, I, T>(mut self, args: I) -> Result<Self, clap::Error>
+    where
+        C: clap::Parser + Any,
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        self.injector.store(C::try_parse_from(args)?);
+        Ok(self)
+    }
+
+    /// Runs every constructor, and returns the finished [`Injector`]. This will panic if any of the
+    /// components cannot be created, see [`Self::try_build_the_world`] for a version that doesn't.
     pub fn build_the_world(self) -> Injector {
-        let metadata_for_normal_types = INJECTION_REGISTRY.iter().map(|create_meta| create_meta());
+        self.try_build_the_world()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Runs every constructor, and returns the finished [`Injector`], or the first error that was
+    /// encountered along the way.
+    pub fn try_build_the_world(self) -> Result<Injector, BuildError> {
+        // Those are built by each `RequestScope` instead
+        let request_scoped = REQUEST_SCOPED_REGISTRY
+            .iter()
+            .map(|scope_meta| scope_meta())
+            .map(|scope_meta| (scope_meta.this, scope_meta.name))
+            .collect::<HashMap<_, _>>();
+        let metadata_for_normal_types = INJECTION_REGISTRY
+            .iter()
+            .map(|create_meta| create_meta())
+            .filter(|meta| !request_scoped.contains_key(&meta.this));
 
         let all_trait_bindings = BINDING_REGISTRY
             .iter()
@@ -37,41 +165,350 @@ impl InjectorBuilder {
             .map(|binding| (binding.trait_object, binding))
             .collect::<MultiMap<_, _>>();
 
-        let metadata_for_bindings = all_trait_bindings.into_iter().flat_map(|(_, bindings)| {
-            if bindings.len() > 1 || bindings[0].is_multi_binding {
-                if bindings.iter().any(|binding| !binding.is_multi_binding) {
-                    panic!("Error registering implementations for {}. Found a mix of #[binding] and #[multi_binding] annotations", bindings[0].name);
+        let mut selections = self.resolve_selections()?;
+        let mut unselected = HashSet::new();
+        let mut metadata_for_bindings = Vec::new();
+        let mut redirects = HashMap::new();
+        for (trait_object, bindings) in all_trait_bindings {
+            let (decorators, bindings): (Vec<_>, Vec<_>) = bindings
+                .into_iter()
+                .partition(|binding| binding.decorates.is_some());
+            let bindings = self.bindings_for_profile(bindings, &mut unselected);
+            let bindings =
+                Self::bindings_for_key(bindings, selections.remove(&trait_object), &mut unselected)?;
+            let bindings = Self::without_overridden_defaults(bindings, &mut unselected);
+            if let Some(first) = bindings.first() {
+                let single_bindings = bindings
+                    .iter()
+                    .filter(|binding| !binding.is_multi_binding)
+                    .count();
+                if single_bindings > 0 && single_bindings < bindings.len() {
+                    return Err(BuildError::MixedBindings {
+                        trait_object: first.name,
+                    });
+                }
+                if single_bindings > 1 {
+                    return Err(BuildError::ConflictingBindings {
+                        trait_object: first.name,
+                        profile: first.profile.map(String::from),
+                    });
                 }
             }
 
-            bindings.into_iter().map(|binding| {
-                InjectMeta {
-                    this: binding.trait_object,
-                    name: binding.name,
-                    dependencies: vec![binding.impl_type],
-                    create: binding.create,
-                    is_multi_binding: binding.is_multi_binding,
+            metadata_for_bindings.extend(Self::stack_decorators(
+                trait_object,
+                bindings,
+                decorators,
+                &mut redirects,
+            )?);
+        }
+
+        if let Some((trait_object, key)) = selections.into_values().next() {
+            return Err(BuildError::NoKeyedBindings { trait_object, key });
+        }
+
+        let metas = metadata_for_normal_types
+            .map(|mut meta| {
+                for (impl_type, &(from, to)) in &redirects {
+                    if meta.this == *impl_type || meta.also_creates.contains(impl_type) {
+                        for dependency in &mut meta.dependencies {
+                            if *dependency == from {
+                                *dependency = to;
+                            }
+                        }
+                    }
                 }
+                (meta.this, meta)
+            })
+            .chain(metadata_for_bindings)
+            .collect::<Vec<_>>();
+        let metas = Self::without_unselected(metas, &unselected);
+        for (_, meta) in &metas {
+            let request_scoped = meta
+                .dependencies
+                .iter()
+                .find_map(|dependency| request_scoped.get(dependency));
+            if let Some(&dependency) = request_scoped {
+                return Err(BuildError::RequestScopedDependency {
+                    component: meta.name,
+                    dependency,
+                });
+            }
+        }
+        self.build_from_metadata(metas.into_iter())
+    }
+
+    /// Works out the key for each selected trait, as `(name of the trait object, key)`.
+    fn resolve_selections(&self) -> Result<HashMap<TypeId, (&'static str, String)>, BuildError> {
+        self.selections
+            .iter()
+            .map(|(&trait_object, selection)| {
+                let key = match &selection.key {
+                    SelectionKey::Given(key) => key.clone(),
+                    #[cfg(feature = "config")]
+                    SelectionKey::FromConfig(path) => self
+                        .key_from_config(path)
+                        .map_err(|error| BuildError::Component {
+                            component: selection.trait_object,
+                            error,
+                        })?,
+                };
+                Ok((trait_object, (selection.trait_object, key)))
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "config")]
+    fn key_from_config(&self, path: &'static str) -> Result<String, crate::ComponentError> {
+        use crate::ComponentError;
+
+        let config = self
+            .injector
+            .try_get_foreign::<crate::config::Config>()
+            .ok_or(ComponentError::MissingConfig)?;
+        config
+            .section(path)
+            .map_err(|error| ComponentError::InvalidConfig {
+                section: path,
+                reason: error.to_string(),
             })
-        });
+    }
 
-        self.build_from_metadata(metadata_for_normal_types.chain(metadata_for_bindings))
+    /// The bindings with the selected key if there was a selection, otherwise the ones without a
+    /// key. The implementation types of every other binding are added to `unselected`.
+    fn bindings_for_key(
+        bindings: Vec<BindingMeta>,
+        selection: Option<(&'static str, String)>,
+        unselected: &mut HashSet<TypeId>,
+    ) -> Result<Vec<BindingMeta>, BuildError> {
+        let (keyed, unkeyed): (Vec<_>, Vec<_>) =
+            bindings.into_iter().partition(|binding| binding.key.is_some());
+        let Some((trait_object, key)) = selection else {
+            unselected.extend(keyed.iter().map(|binding| binding.impl_type));
+            return Ok(unkeyed);
+        };
+        if keyed.is_empty() {
+            return Err(BuildError::NoKeyedBindings { trait_object, key });
+        }
+
+        let (chosen, others): (Vec<_>, Vec<_>) = keyed
+            .into_iter()
+            .partition(|binding| binding.key == Some(key.as_str()));
+        if chosen.is_empty() {
+            let mut valid_keys = others
+                .iter()
+                .filter_map(|binding| binding.key)
+                .collect::<Vec<_>>();
+            valid_keys.sort_unstable();
+            return Err(BuildError::UnknownBindingKey {
+                trait_object,
+                key,
+                valid_keys,
+            });
+        }
+
+        unselected.extend(
+            others
+                .iter()
+                .chain(unkeyed.iter())
+                .map(|binding| binding.impl_type),
+        );
+        Ok(chosen)
     }
 
-    fn build_from_metadata(mut self, metas: impl Iterator<Item = InjectMeta>) -> Injector {
-        let metas = metas
-            .map(|meta| (meta.this, meta))
+    /// Drops the `#[default_binding]`s if there are any other bindings, adding their implementation
+    /// types to `unselected`.
+    fn without_overridden_defaults(
+        bindings: Vec<BindingMeta>,
+        unselected: &mut HashSet<TypeId>,
+    ) -> Vec<BindingMeta> {
+        if bindings.iter().all(|binding| binding.is_default) {
+            return bindings;
+        }
+
+        let (defaults, overrides): (Vec<_>, Vec<_>) =
+            bindings.into_iter().partition(|binding| binding.is_default);
+        unselected.extend(defaults.iter().map(|binding| binding.impl_type));
+        overrides
+    }
+
+    /// Decorators are stacked on top of the binding for their trait, lowest order first. Every layer
+    /// is stored as the trait object, so the outermost one is what the injector hands out, but they
+    /// each get their own key in the graph so that they are created in order. `redirects` records
+    /// that each decorator's implementation should depend on the layer beneath it, rather than on
+    /// the trait object itself.
+    ///
+    /// Returns the binding metadata, paired with the key for it in the graph.
+    fn stack_decorators(
+        trait_object: TypeId,
+        bindings: Vec<BindingMeta>,
+        mut decorators: Vec<BindingMeta>,
+        redirects: &mut HashMap<TypeId, (TypeId, TypeId)>,
+    ) -> Result<Vec<(TypeId, InjectMeta)>, BuildError> {
+        let Some(first) = decorators.first() else {
+            return Ok(bindings
+                .into_iter()
+                .map(|binding| (trait_object, Self::inject_meta_for_binding(binding)))
+                .collect());
+        };
+        let name = first.name;
+        if bindings.len() != 1 || bindings[0].is_multi_binding {
+            return Err(BuildError::InvalidDecorators {
+                trait_object: name,
+                reason: "decorators need exactly one #[binding] to wrap".to_string(),
+            });
+        }
+
+        let decoration = |binding: &BindingMeta| binding.decorates.expect("only decorators");
+        decorators.sort_by_key(|decorator| decoration(decorator).order);
+        if let Some(pair) = decorators
+            .windows(2)
+            .find(|pair| decoration(&pair[0]).order == decoration(&pair[1]).order)
+        {
+            return Err(BuildError::InvalidDecorators {
+                trait_object: name,
+                reason: format!("more than one decorator has order {}", decoration(&pair[0]).order),
+            });
+        }
+
+        for decorator in &decorators {
+            redirects.insert(
+                decorator.impl_type,
+                (trait_object, decoration(decorator).input),
+            );
+        }
+        let keys = decorators
+            .iter()
+            .map(|decorator| decoration(decorator).input)
+            .chain(std::iter::once(trait_object))
+            .collect::<Vec<_>>();
+        Ok(keys
+            .into_iter()
+            .zip(bindings.into_iter().chain(decorators))
+            .map(|(key, binding)| (key, Self::inject_meta_for_binding(binding)))
+            .collect())
+    }
+
+    fn inject_meta_for_binding(binding: BindingMeta) -> InjectMeta {
+        InjectMeta {
+            this: binding.trait_object,
+            name: binding.name,
+            also_creates: Vec::new(),
+            dependencies: vec![binding.impl_type],
+            collections: Vec::new(),
+            create: binding.create,
+            is_multi_binding: binding.is_multi_binding,
+            order: binding.order,
+        }
+    }
+
+    /// Drops the components in `unselected`, along with anything that is only needed by them. Each
+    /// component is paired with its key in the graph.
+    fn without_unselected(
+        metas: Vec<(TypeId, InjectMeta)>,
+        unselected: &HashSet<TypeId>,
+    ) -> Vec<(TypeId, InjectMeta)> {
+        if unselected.is_empty() {
+            return metas;
+        }
+
+        let providers = metas
+            .iter()
+            .enumerate()
+            .flat_map(|(position, (key, meta))| {
+                std::iter::once(*key)
+                    .chain(meta.also_creates.iter().copied())
+                    .map(move |this| (this, position))
+            })
             .collect::<MultiMap<_, _>>();
+        let reachable_from = |starts: Vec<usize>| {
+            let mut reachable = HashSet::new();
+            let mut to_visit = starts;
+            while let Some(position) = to_visit.pop() {
+                if !reachable.insert(position) {
+                    continue;
+                }
+                for dependency in &metas[position].1.dependencies {
+                    to_visit.extend(providers.get_vec(dependency).into_iter().flatten());
+                }
+            }
+            reachable
+        };
+
+        let reachable_from_unselected = reachable_from(
+            unselected
+                .iter()
+                .flat_map(|this| providers.get_vec(this).into_iter().flatten())
+                .copied()
+                .collect(),
+        );
+        let needed = reachable_from(
+            (0..metas.len())
+                .filter(|position| !reachable_from_unselected.contains(position))
+                .collect(),
+        );
+
+        metas
+            .into_iter()
+            .enumerate()
+            .filter(|(position, _)| needed.contains(position))
+            .map(|(_, meta)| meta)
+            .collect()
+    }
+
+    /// The bindings for the active profile if there are any, otherwise the ones without a profile.
+    /// The implementation types of every other binding are added to `unselected`.
+    fn bindings_for_profile(
+        &self,
+        bindings: Vec<BindingMeta>,
+        unselected: &mut HashSet<TypeId>,
+    ) -> Vec<BindingMeta> {
+        let (active, inactive): (Vec<_>, Vec<_>) = bindings
+            .into_iter()
+            .partition(|binding| {
+                binding.profile.is_none() || binding.profile == self.profile.as_deref()
+            });
+        let (for_profile, defaults): (Vec<_>, Vec<_>) =
+            active.into_iter().partition(|binding| binding.profile.is_some());
+
+        unselected.extend(inactive.iter().map(|binding| binding.impl_type));
+        if for_profile.is_empty() {
+            defaults
+        } else {
+            unselected.extend(defaults.iter().map(|binding| binding.impl_type));
+            for_profile
+        }
+    }
+
+    /// Builds every component, given paired with its key in the graph. That is the type it creates,
+    /// apart from decorated bindings, see [`Self::stack_decorators`].
+    fn build_from_metadata(
+        mut self,
+        metas: impl Iterator<Item = (TypeId, InjectMeta)>,
+    ) -> Result<Injector, BuildError> {
+        let metas = metas.collect::<MultiMap<_, _>>();
 
         let sorted = Self::topological_sort(metas);
         for meta in sorted {
-            self.injector.build_and_store(&meta);
+            self.injector.build_and_store(&meta)?;
         }
 
-        self.injector
+        Ok(self.injector)
     }
 
-    fn topological_sort(mut graph: MultiMap<TypeId, InjectMeta>) -> Vec<InjectMeta> {
+    pub(super) fn topological_sort(mut graph: MultiMap<TypeId, InjectMeta>) -> Vec<InjectMeta> {
+        // Constructors that return a tuple live in the graph under their first element, so depending
+        // on any of the other elements needs to be redirected there.
+        let aliases = graph
+            .iter_all()
+            .flat_map(|(&this, metas)| {
+                metas
+                    .iter()
+                    .flat_map(|meta| meta.also_creates.iter())
+                    .map(move |&other| (other, this))
+            })
+            .collect::<HashMap<_, _>>();
+
         // As we go through, we will pull items out of the graph and push them onto this list
         let mut creation_order = Vec::new();
 
@@ -88,6 +525,7 @@ impl InjectorBuilder {
             while let Some(to_visit) = dfs_queue.pop() {
                 match to_visit {
                     VisitType::BeforeChildren(this_type) => {
+                        let this_type = aliases.get(&this_type).copied().unwrap_or(this_type);
                         let Some(to_visit_metas) = graph.remove(&this_type) else {
                             // If the node has been removed from the graph, then its already queued up to be
                             // created ...or it's not injectable in the first place, which is unfortunate,
@@ -115,3 +553,257 @@ impl InjectorBuilder {
         creation_order
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use super::*;
+    use crate::{ComponentError, derive_api::Decoration};
+
+    struct First;
+    struct Second;
+    struct DependsOnSecond;
+
+    unsafe fn unreachable_create(_: &Injector) -> Result<Box<dyn Any>, ComponentError> {
+        unreachable!()
+    }
+
+    fn meta<T: Any>(also_creates: Vec<TypeId>, dependencies: Vec<TypeId>) -> InjectMeta {
+        InjectMeta {
+            this: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+            also_creates,
+            dependencies,
+            collections: Vec::new(),
+            create: unreachable_create,
+            is_multi_binding: false,
+            order: 0,
+        }
+    }
+
+    #[test]
+    fn dependencies_of_unselected_components_are_dropped_unless_needed_elsewhere() {
+        struct Unselected;
+        struct OnlyForUnselected;
+        struct Shared;
+        struct Selected;
+
+        let metas = vec![
+            meta::<Unselected>(
+                vec![],
+                vec![TypeId::of::<OnlyForUnselected>(), TypeId::of::<Shared>()],
+            ),
+            meta::<OnlyForUnselected>(vec![], vec![]),
+            meta::<Shared>(vec![], vec![]),
+            meta::<Selected>(vec![], vec![TypeId::of::<Shared>()]),
+        ];
+        let unselected = HashSet::from([TypeId::of::<Unselected>()]);
+
+        let metas = metas.into_iter().map(|meta| (meta.this, meta)).collect();
+        let kept = InjectorBuilder::without_unselected(metas, &unselected)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert_eq!(kept, [TypeId::of::<Shared>(), TypeId::of::<Selected>()]);
+    }
+
+    fn binding<T: Any>(profile: Option<&'static str>) -> BindingMeta {
+        BindingMeta {
+            trait_object: TypeId::of::<&'static dyn Any>(),
+            name: "dyn Any",
+            impl_type: TypeId::of::<T>(),
+            is_multi_binding: false,
+            order: 0,
+            is_default: false,
+            decorates: None,
+            profile,
+            key: None,
+            create: unreachable_create,
+        }
+    }
+
+    #[test]
+    fn decorators_are_stacked_in_order_on_top_of_the_binding() {
+        struct Outer;
+        struct Inner;
+        let decorator = |order, input| BindingMeta {
+            decorates: Some(Decoration { input, order }),
+            ..binding::<Outer>(None)
+        };
+        let inner_input = TypeId::of::<Inner>();
+        let outer_input = TypeId::of::<Outer>();
+        let trait_object = TypeId::of::<&'static dyn Any>();
+
+        let mut redirects = HashMap::new();
+        let keys = InjectorBuilder::stack_decorators(
+            trait_object,
+            vec![binding::<First>(None)],
+            vec![decorator(2, outer_input), decorator(1, inner_input)],
+            &mut redirects,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+
+        assert_eq!(keys, [inner_input, outer_input, trait_object]);
+    }
+
+    #[test]
+    fn bindings_for_the_active_profile_replace_the_defaults() {
+        let bindings = || {
+            vec![
+                binding::<First>(None),
+                binding::<Second>(Some("prod")),
+                binding::<DependsOnSecond>(Some("test")),
+            ]
+        };
+        let selected = |builder: InjectorBuilder| {
+            builder
+                .bindings_for_profile(bindings(), &mut HashSet::new())
+                .into_iter()
+                .map(|binding| binding.impl_type)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(selected(Injector::builder()), [TypeId::of::<First>()]);
+        assert_eq!(selected(Injector::builder().profile("prod")), [TypeId::of::<Second>()]);
+        assert_eq!(selected(Injector::builder().profile("other")), [TypeId::of::<First>()]);
+    }
+
+    #[test]
+    fn bindings_for_other_profiles_are_never_constructed() {
+        unsafe fn create<T: Any + Default>(_: &Injector) -> Result<Box<dyn Any>, ComponentError> {
+            Ok(Box::new(T::default()))
+        }
+        struct Fallback;
+        #[derive(Default)]
+        struct Prod;
+        struct Test;
+
+        let bindings = vec![
+            binding::<Fallback>(None),
+            binding::<Prod>(Some("prod")),
+            binding::<Test>(Some("test")),
+        ];
+        let builder = Injector::builder().profile("prod");
+        let mut unselected = HashSet::new();
+        let selected = builder.bindings_for_profile(bindings, &mut unselected);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(unselected, HashSet::from([TypeId::of::<Fallback>(), TypeId::of::<Test>()]));
+
+        // Only `Prod` can be built, the others would panic
+        let metas = vec![
+            InjectMeta {
+                create: create::<Prod>,
+                ..meta::<Prod>(vec![], vec![])
+            },
+            meta::<Fallback>(vec![], vec![]),
+            meta::<Test>(vec![], vec![]),
+        ];
+        let metas = metas.into_iter().map(|meta| (meta.this, meta)).collect();
+        let kept = InjectorBuilder::without_unselected(metas, &unselected);
+        let injector = builder.build_from_metadata(kept.into_iter()).unwrap();
+        assert!(injector.has_own(TypeId::of::<Prod>()));
+    }
+
+    #[test]
+    fn default_bindings_are_only_kept_without_a_normal_binding() {
+        let default = || BindingMeta {
+            is_default: true,
+            ..binding::<Second>(None)
+        };
+        let kept = |bindings| {
+            let mut unselected = HashSet::new();
+            let kept = InjectorBuilder::without_overridden_defaults(bindings, &mut unselected)
+                .into_iter()
+                .map(|binding| binding.impl_type)
+                .collect::<Vec<_>>();
+            (kept, unselected)
+        };
+
+        let (overridden, unselected) = kept(vec![default(), binding::<First>(None)]);
+        assert_eq!(overridden, [TypeId::of::<First>()]);
+        assert_eq!(unselected, HashSet::from([TypeId::of::<Second>()]));
+
+        let (alone, unselected) = kept(vec![default()]);
+        assert_eq!(alone, [TypeId::of::<Second>()]);
+        assert!(unselected.is_empty());
+    }
+
+    #[test]
+    fn selected_keys_must_belong_to_a_keyed_binding() {
+        let keyed = |key| BindingMeta {
+            key: Some(key),
+            ..binding::<First>(None)
+        };
+        let select = |bindings, key: &str| {
+            let selection = Some(("dyn Any", key.to_string()));
+            InjectorBuilder::bindings_for_key(bindings, selection, &mut HashSet::new())
+        };
+
+        let chosen = select(vec![keyed("local"), keyed("s3")], "s3").unwrap();
+        assert_eq!(chosen.len(), 1);
+        assert_eq!(chosen[0].key, Some("s3"));
+        let error = select(vec![keyed("s3"), keyed("local")], "ftp").err().unwrap();
+        let BuildError::UnknownBindingKey { valid_keys, .. } = error else {
+            panic!("Unexpected error {}", error)
+        };
+        assert_eq!(valid_keys, ["local", "s3"]);
+
+        let error = select(vec![binding::<First>(None)], "s3").err().unwrap();
+        assert!(matches!(error, BuildError::NoKeyedBindings { key, .. } if key == "s3"));
+    }
+
+    #[test]
+    fn selecting_a_key_for_a_trait_without_bindings_is_an_error() {
+        trait Unbound {}
+
+        let error = Injector::builder()
+            .select::<dyn Unbound>("s3")
+            .try_build_the_world()
+            .err()
+            .unwrap();
+        assert!(matches!(error, BuildError::NoKeyedBindings { key, .. } if key == "s3"));
+    }
+
+    #[test]
+    fn depending_on_a_later_tuple_element_sorts_after_the_tuple() {
+        let graph = [
+            meta::<DependsOnSecond>(vec![], vec![TypeId::of::<Second>()]),
+            meta::<First>(vec![TypeId::of::<Second>()], vec![]),
+        ]
+        .into_iter()
+        .map(|meta| (meta.this, meta))
+        .collect();
+
+        let sorted = InjectorBuilder::topological_sort(graph)
+            .into_iter()
+            .map(|meta| meta.this)
+            .collect::<Vec<_>>();
+        assert_eq!(sorted, [TypeId::of::<First>(), TypeId::of::<DependsOnSecond>()]);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn arguments_are_parsed_before_building() {
+        #[derive(clap::Parser)]
+        struct Args {
+            #[arg(long)]
+            port: u16,
+        }
+
+        let injector = Injector::builder()
+            .try_with_args_from::<Args, _, _>(["server", "--port", "9000"])
+            .unwrap()
+            .build_the_world();
+        assert_eq!(injector.get_foreign::<Args>().port, 9000);
+
+        let error = Injector::builder()
+            .try_with_args_from::<Args, _, _>(["server", "--help"])
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), clap::error::ErrorKind::DisplayHelp);
+    }
+}
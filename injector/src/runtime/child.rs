@@ -0,0 +1,176 @@
+use std::{any::Any, marker::PhantomData, ops::Deref};
+
+use multimap::MultiMap;
+
+use super::{BuildError, Injector, InjectorBuilder};
+use crate::{
+    Injectable,
+    derive_api::{BINDING_REGISTRY, INJECTION_REGISTRY, InjectMeta, InjectableStatic},
+};
+
+/// A builder for a [`ChildInjector`], from [`Injector::child`]. Add values with
+/// [`Self::inject_value`] to override the parent's, pick the components that should be built
+/// again with [`Self::construct`], and then call [`Self::build`].
+pub struct ChildInjectorBuilder<'p> {
+    injector: Injector,
+    metas: Vec<InjectMeta>,
+    parent: PhantomData<&'p Injector>,
+}
+
+/// An [`Injector`] that can also supply every component of its parent, see [`Injector::child`].
+///
+/// Dropping it only drops the components that were added to the child, in the reverse of the
+/// order they were created in. The parent and everything in it are left alone.
+pub struct ChildInjector<'p> {
+    injector: Injector,
+    parent: PhantomData<&'p Injector>,
+}
+
+impl<'p> ChildInjectorBuilder<'p> {
+    pub(super) fn new(parent: &'p Injector) -> Self {
+        ChildInjectorBuilder {
+            // SAFETY: The child ends up in a `ChildInjector<'p>`, which cannot outlive the parent.
+            injector: unsafe { Injector::empty_child(parent) },
+            metas: Vec::new(),
+            parent: PhantomData,
+        }
+    }
+
+    /// Add a value to the child, like [`InjectorBuilder::inject_value`]. This takes precedence
+    /// over any value of the same type in the parent.
+    pub fn inject_value<I>(mut self, value: I) -> Self
+    where
+        I: for<'a> Injectable<'a> + InjectableStatic,
+    {
+        self.injector.store(value);
+        self
+    }
+
+    /// Add a foreign value to the child, like [`InjectorBuilder::inject_foreign`]. This takes
+    /// precedence over any value of the same type in the parent.
+    pub fn inject_foreign<T>(mut self, value: T) -> Self
+    where
+        T: Any,
+    {
+        self.injector.store(value);
+        self
+    }
+
+    /// Run the constructor for `I` again, so that the child gets its own `I` built from the
+    /// child's components where it has them, and the parent's everywhere else. Anything else the
+    /// same constructor returns is replaced along with it.
+    ///
+    /// Trait objects always come from the parent, as the child can't tell which of the bindings
+    /// for a trait its parent picked. So this will panic if `I` implements a trait with
+    /// `#[binding]` or `#[multi_binding]`, rather than leave the parent's trait object pointing at
+    /// the parent's `I`. It will also panic if `I` has no constructor, such as when it is marked
+    /// `#[has_constructor]` and only ever added with `inject_value`.
+    pub fn construct<'a, I: Injectable<'a>>(mut self) -> Self {
+        let this = std::any::TypeId::of::<I::Static>();
+        let binding = BINDING_REGISTRY
+            .iter()
+            .map(|create_binding| create_binding())
+            .find(|binding| binding.impl_type == this);
+        if let Some(binding) = binding {
+            panic!(
+                "Unable to construct {} in a child injector, as it is bound to {}, which would \
+                still come from the parent.",
+                std::any::type_name::<I::Static>(),
+                binding.name
+            )
+        }
+
+        let metas = INJECTION_REGISTRY
+            .iter()
+            .map(|create_meta| create_meta())
+            .filter(|meta| meta.this == this || meta.also_creates.contains(&this))
+            .collect::<Vec<_>>();
+        if metas.is_empty() {
+            panic!(
+                "Unable to construct {} in a child injector, as it has no constructor.",
+                std::any::type_name::<I::Static>()
+            )
+        }
+
+        self.metas.extend(metas);
+        self
+    }
+
+    /// Runs the constructors picked with [`Self::construct`], and returns the finished
+    /// [`ChildInjector`]. This will panic if any of the components cannot be created, see
+    /// [`Self::try_build`] for a version that doesn't.
+    pub fn build(self) -> ChildInjector<'p> {
+        self.try_build()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Runs the constructors picked with [`Self::construct`], and returns the finished
+    /// [`ChildInjector`], or the first error that was encountered along the way. That includes a
+    /// dependency that neither the child nor its parent has.
+    pub fn try_build(mut self) -> Result<ChildInjector<'p>, BuildError> {
+        let metas = self
+            .metas
+            .into_iter()
+            .map(|meta| (meta.this, meta))
+            .collect::<MultiMap<_, _>>();
+
+        // Dependencies on components that aren't being constructed again are skipped by the sort,
+        // and are found in the child or its parent when the constructor asks for them.
+        for meta in InjectorBuilder::topological_sort(metas) {
+            self.injector.check_dependencies(&meta)?;
+            self.injector.build_and_store(&meta)?;
+        }
+
+        Ok(ChildInjector {
+            injector: self.injector,
+            parent: PhantomData,
+        })
+    }
+}
+
+impl Deref for ChildInjector<'_> {
+    type Target = Injector;
+
+    fn deref(&self) -> &Injector {
+        &self.injector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::*;
+    use crate::ComponentError;
+
+    #[test]
+    fn missing_dependencies_are_an_error() {
+        struct Missing;
+        struct NeedsMissing;
+        unsafe fn create(_: &Injector) -> Result<Box<dyn Any>, ComponentError> {
+            panic!("built despite a missing dependency")
+        }
+
+        let parent = Injector::new();
+        let mut builder = parent.child().inject_foreign(());
+        builder.metas.push(InjectMeta {
+            this: TypeId::of::<NeedsMissing>(),
+            name: "NeedsMissing",
+            also_creates: Vec::new(),
+            dependencies: vec![TypeId::of::<()>(), TypeId::of::<Missing>()],
+            collections: Vec::new(),
+            create,
+            is_multi_binding: false,
+            order: 0,
+        });
+
+        let error = builder.try_build().err().unwrap();
+        assert!(matches!(
+            error,
+            BuildError::MissingDependency {
+                component: "NeedsMissing",
+                dependency: None,
+            }
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{error::Error, fmt};
+
+/// An error that stopped [`super::InjectorBuilder::try_build_the_world`] from building every
+/// component.
+#[derive(Debug)]
+pub enum BuildError {
+    /// A trait had both `#[binding]` and `#[multi_binding]` implementations registered for it.
+    MixedBindings { trait_object: &'static str },
+    /// A trait had more than one `#[binding]` implementation registered for it in the same profile
+    /// (or more than one without a profile).
+    ConflictingBindings {
+        trait_object: &'static str,
+        profile: Option<String>,
+    },
+    /// A trait was given a key with `InjectorBuilder::select` that none of its
+    /// `#[binding(key = "...")]` implementations have.
+    UnknownBindingKey {
+        trait_object: &'static str,
+        key: String,
+        valid_keys: Vec<&'static str>,
+    },
+    /// A trait was given a key with `InjectorBuilder::select`, but it has no
+    /// `#[binding(key = "...")]` implementations at all.
+    NoKeyedBindings {
+        trait_object: &'static str,
+        key: String,
+    },
+    /// The `#[decorates(dyn Trait)]` implementations for a trait could not be stacked up.
+    InvalidDecorators {
+        trait_object: &'static str,
+        reason: String,
+    },
+    /// A component that is shared by every request depended on an `#[injectable(scope = request)]`
+    /// one, which only exists inside a `RequestScope`.
+    RequestScopedDependency {
+        component: &'static str,
+        dependency: &'static str,
+    },
+    /// A component built by a `ChildInjector` or `RequestScope` depended on something that neither
+    /// it nor its parent has. The dependency is only named when it has a constructor or binding, or
+    /// is request scoped.
+    MissingDependency {
+        component: &'static str,
+        dependency: Option<&'static str>,
+    },
+    /// A component was asked for from a `RequestScope`, but neither it nor its parent has one.
+    MissingComponent { component: &'static str },
+    /// Building a request scoped component needed that same component, through the listed chain of
+    /// request scoped dependencies.
+    DependencyCycle { cycle: Vec<&'static str> },
+    /// A component could not be created.
+    Component {
+        component: &'static str,
+        error: ComponentError,
+    },
+}
+
+/// The reason a single component could not be created.
+#[derive(Debug)]
+pub enum ComponentError {
+    /// A field marked `#[inject(env = "...")]` had no default, and the variable was not set.
+    MissingEnvironmentVariable { variable: &'static str },
+    /// A field marked `#[inject(env = "...")]` could not be parsed from the variable (or from its
+    /// default, if the variable was not set).
+    InvalidEnvironmentVariable {
+        variable: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// A `#[config(section = "...")]` component was built, but no config was added with
+    /// `InjectorBuilder::with_config`.
+    MissingConfig,
+    /// A `#[config(section = "...")]` component could not be deserialised from its section, or
+    /// failed its validation.
+    InvalidConfig {
+        section: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MixedBindings { trait_object } => write!(
+                f,
+                "Error registering implementations for {}. Found a mix of #[binding] and #[multi_binding] annotations",
+                trait_object
+            ),
+            BuildError::ConflictingBindings {
+                trait_object,
+                profile: Some(profile),
+            } => write!(
+                f,
+                "Error registering implementations for {}. Found more than one #[binding] for the {} profile",
+                trait_object, profile
+            ),
+            BuildError::ConflictingBindings {
+                trait_object,
+                profile: None,
+            } => write!(
+                f,
+                "Error registering implementations for {}. Found more than one #[binding] without a profile",
+                trait_object
+            ),
+            BuildError::UnknownBindingKey {
+                trait_object,
+                key,
+                valid_keys,
+            } => {
+                let valid_keys = valid_keys
+                    .iter()
+                    .map(|key| format!("\"{}\"", key))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "Unknown key \"{}\" for {}. Valid keys are {}",
+                    key, trait_object, valid_keys
+                )
+            }
+            BuildError::NoKeyedBindings { trait_object, key } => write!(
+                f,
+                "Unable to select \"{}\" for {}, as it has no #[binding(key = \"...\")] implementations",
+                key, trait_object
+            ),
+            BuildError::InvalidDecorators {
+                trait_object,
+                reason,
+            } => write!(f, "Unable to decorate {}: {}", trait_object, reason),
+            BuildError::RequestScopedDependency {
+                component,
+                dependency,
+            } => write!(
+                f,
+                "Unable to create {}, as it depends on {}, which is request scoped. Only other request scoped components can depend on it",
+                component, dependency
+            ),
+            BuildError::MissingDependency {
+                component,
+                dependency: Some(dependency),
+            } => write!(
+                f,
+                "Unable to create {}, as {} is missing from the injector and its parent",
+                component, dependency
+            ),
+            BuildError::MissingDependency {
+                component,
+                dependency: None,
+            } => write!(
+                f,
+                "Unable to create {}, as one of its dependencies is missing from the injector and its parent",
+                component
+            ),
+            BuildError::MissingComponent { component } => write!(
+                f,
+                "Unable to get an instance of {} from the request scope or its parent",
+                component
+            ),
+            BuildError::DependencyCycle { cycle } => write!(
+                f,
+                "Unable to create {}, as it depends on itself: {}",
+                cycle[0],
+                cycle.join(" -> ")
+            ),
+            BuildError::Component { component, error } => {
+                write!(f, "Unable to create {}: {}", component, error)
+            }
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BuildError::MixedBindings { .. }
+            | BuildError::ConflictingBindings { .. }
+            | BuildError::UnknownBindingKey { .. }
+            | BuildError::NoKeyedBindings { .. }
+            | BuildError::InvalidDecorators { .. }
+            | BuildError::RequestScopedDependency { .. }
+            | BuildError::MissingDependency { .. }
+            | BuildError::MissingComponent { .. }
+            | BuildError::DependencyCycle { .. } => None,
+            BuildError::Component { error, .. } => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for ComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentError::MissingEnvironmentVariable { variable } => {
+                write!(f, "environment variable {} is not set", variable)
+            }
+            ComponentError::InvalidEnvironmentVariable {
+                variable,
+                value,
+                reason,
+            } => write!(
+                f,
+                "unable to parse {:?} from environment variable {}: {}",
+                value, variable, reason
+            ),
+            ComponentError::MissingConfig => write!(
+                f,
+                "no config was added to the injector, see InjectorBuilder::with_config"
+            ),
+            ComponentError::InvalidConfig { section, reason } => {
+                write!(f, "invalid config in section {}: {}", section, reason)
+            }
+        }
+    }
+}
+
+impl Error for ComponentError {}
@@ -1,9 +1,19 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
 
-use super::{builder::InjectorBuilder, unsafe_storage::UnsafeStore};
+use super::{
+    BuildError, ChildInjectorBuilder, Reload, RequestScopeBuilder, builder::InjectorBuilder,
+    unsafe_storage::UnsafeStore,
+};
 use crate::{
-    Injectable,
-    derive_api::{InjectMeta, InjectableStatic},
+    Injectable, Owned,
+    derive_api::{
+        BINDING_REGISTRY, Components, INJECTION_REGISTRY, InjectMeta, InjectableStatic,
+        REQUEST_SCOPED_REGISTRY, Reloading, Shared,
+    },
 };
 
 /// The runtime that manages our injections. You should only need a single [`Injector`], that is
@@ -15,7 +25,20 @@ use crate::{
 pub struct Injector {
     items: UnsafeStore,
     index: HashMap<TypeId, usize>,
-    multi_bindings_index: HashMap<TypeId, Vec<usize>>,
+    // Each position is paired with its order, and kept sorted by it
+    multi_bindings_index: HashMap<TypeId, Vec<(i32, usize)>>,
+    // These point into `items`, see the safety comment in `build_and_store`.
+    reloadables: Vec<&'static dyn Reload>,
+    // The `Arc<dyn Trait>` for each binding whose implementation is `Owned`, keyed like `index`
+    shared_index: HashMap<TypeId, usize>,
+    // Anything missing from this injector is looked up here, see `Self::child`
+    parent: Option<&'static Injector>,
+}
+
+impl Default for Injector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Injector {
@@ -26,25 +49,56 @@ impl Injector {
     }
 
     pub fn builder() -> InjectorBuilder {
-        InjectorBuilder::new(Injector {
+        InjectorBuilder::new(Self::empty(None))
+    }
+
+    /// Start building a child of this injector, which can supply everything this one can, along
+    /// with any components added to the child itself. Those take precedence over the ones here, so
+    /// a child can override components for a single tenant or job without rebuilding the world.
+    /// Trait objects are the exception, and always come from here, see
+    /// [`ChildInjectorBuilder::construct`].
+    pub fn child(&self) -> ChildInjectorBuilder<'_> {
+        ChildInjectorBuilder::new(self)
+    }
+
+    /// Start building a scope for a single request, which builds the `#[injectable(scope =
+    /// request)]` components as they are needed, and gets everything else from this injector.
+    pub fn request_scope(&self) -> RequestScopeBuilder<'_> {
+        RequestScopeBuilder::new(self)
+    }
+
+    /// An injector with no components, which looks up anything it is missing in `parent`.
+    ///
+    /// # Safety
+    /// The returned injector must not outlive `parent`, see [`super::ChildInjector`].
+    pub(super) unsafe fn empty_child(parent: &Injector) -> Injector {
+        // SAFETY: The caller guarantees that the child won't outlive its parent.
+        let parent = unsafe { &*(parent as *const Injector) };
+        Self::empty(Some(parent))
+    }
+
+    fn empty(parent: Option<&'static Injector>) -> Injector {
+        Injector {
             items: UnsafeStore::new(),
             index: HashMap::new(),
             multi_bindings_index: HashMap::new(),
-        })
+            reloadables: Vec::new(),
+            shared_index: HashMap::new(),
+            parent,
+        }
     }
 
     /// Fetch an item from the injector cache. This will panic if for some reason the object does
     /// not exist.
     pub fn get<'a, I: Injectable<'a>>(&'a self) -> &'a I {
-        let Some(&position) = self.index.get(&TypeId::of::<I::Static>()) else {
+        let Some(static_item) = self.find(TypeId::of::<I::Static>()) else {
             panic!(
                 "Unable to get an instance of {} from the injector.",
                 std::any::type_name::<I::Static>()
             )
         };
 
-        let static_item: &I::Static = UnsafeStore::get(&self.items, position)
-            .unwrap() // any usize in the `index` has to map to an item in the UnsafeStore
+        let static_item: &I::Static = static_item
             .downcast_ref()
             .unwrap(); // We check that the `dyn Any`s match up with what they say they do on insert
 
@@ -53,18 +107,67 @@ impl Injector {
         static_item.downcast()
     }
 
+    /// Fetch an item from the injector cache as an `Arc`, which can outlive the injector. Only
+    /// [`Owned`] types can be fetched like this, as anything else borrows from the injector. Those
+    /// can hold their own dependencies as `Arc<T>` fields to keep them alive in the same way. This
+    /// will panic if for some reason the object does not exist.
+    pub fn get_arc<T: Owned>(&self) -> Arc<T> {
+        let found = self.lineage().find_map(|injector| {
+            let &position = injector.index.get(&TypeId::of::<T>())?;
+            // any usize in the `index` has to map to an item in the UnsafeStore
+            Some(UnsafeStore::get_arc(&injector.items, position).unwrap())
+        });
+        let Some(item) = found else {
+            panic!(
+                "Unable to get an instance of {} from the injector.",
+                std::any::type_name::<T>()
+            )
+        };
+
+        assert!(
+            item.is::<T>(),
+            "We check that the `dyn Any`s match up with what they say they do on insert"
+        );
+        // SAFETY: We just checked that this is a `T`. It is `Owned`, so it doesn't borrow anything
+        // from the injector and can safely outlive it, see the docs for `UnsafeStore`.
+        unsafe { Arc::from_raw(Arc::into_raw(item).cast::<T>()) }
+    }
+
+    /// Fetch a foreign type (one that does not implement [`crate::Injectable`], typically because
+    /// it comes from another crate) from the injector cache. These are registered with
+    /// `#[provides]` or [`InjectorBuilder::inject_foreign`]. Standard library types such as
+    /// `PathBuf` or `Vec<T>` are [`crate::Injectable`], so they can be fetched with [`Self::get`].
+    /// This will panic if for some reason the object does not exist.
+    pub fn get_foreign<T: Any>(&self) -> &T {
+        let Some(item) = self.try_get_foreign() else {
+            panic!(
+                "Unable to get an instance of {} from the injector.",
+                std::any::type_name::<T>()
+            )
+        };
+
+        item
+    }
+
+    pub(crate) fn try_get_foreign<T: Any>(&self) -> Option<&T> {
+        let item = self
+            .find(TypeId::of::<T>())?
+            .downcast_ref()
+            .unwrap(); // We check that the `dyn Any`s match up with what they say they do on insert
+        Some(item)
+    }
+
     /// Fetch a trait object from the injector cache. This will panic if no binding has been made
     /// to that trait with `#[binding]`.
     pub fn get_trait_object<T: ?Sized + 'static>(&self) -> &T {
-        let Some(&position) = self.index.get(&TypeId::of::<&'static T>()) else {
+        let Some(boxed_trait_object) = self.find(TypeId::of::<&'static T>()) else {
             panic!(
                 "Unable to get an instance of {} from the injector.",
                 std::any::type_name::<T>()
             )
         };
 
-        let boxed_trait_object: &&'static T = UnsafeStore::get(&self.items, position)
-            .unwrap() // any usize in the `index` has to map to an item in the UnsafeStore
+        let boxed_trait_object: &&'static T = boxed_trait_object
             .downcast_ref()
             .unwrap(); // We check that the `dyn Any`s match up with what they say they do on insert
 
@@ -74,18 +177,52 @@ impl Injector {
         boxed_trait_object
     }
 
+    /// Like [`Self::get_trait_object`], but as an `Arc` that can outlive the injector. This will
+    /// panic unless the `#[binding]` is for an [`Owned`] type, as anything else borrows from the
+    /// injector.
+    pub fn get_trait_object_arc<T: ?Sized + 'static>(&self) -> Arc<T> {
+        let found = self.lineage().find_map(|injector| {
+            let &position = injector.shared_index.get(&TypeId::of::<&'static T>())?;
+            Some((injector, position))
+        });
+        let Some((injector, position)) = found else {
+            panic!(
+                "Unable to get an Arc of {} from the injector. Only bindings for types without a \
+                lifetime can be shared.",
+                std::any::type_name::<T>()
+            )
+        };
+
+        let shared: &Arc<T> = UnsafeStore::get(&injector.items, position)
+            .unwrap() // any usize in the `index` has to map to an item in the UnsafeStore
+            .downcast_ref()
+            .unwrap(); // We check that the `dyn Any`s match up with what they say they do on insert
+        Arc::clone(shared)
+    }
+
     /// Fetch all trait objects implementing a given trait from the injector cache. This will panic
     /// if no bindings have been made to that trait with `#[multi_binding]`.
     pub fn get_all_trait_objects<T: ?Sized + 'static>(&self) -> impl Iterator<Item = &T> {
-        let Some(positions) = self.multi_bindings_index.get(&TypeId::of::<&'static T>()) else {
+        let Some(trait_objects) = self.try_get_all_trait_objects() else {
             panic!(
                 "Unable to get any instances of {} from the injector.",
                 std::any::type_name::<T>()
             )
         };
 
-        positions.iter().map(|&position| {
-            let boxed_trait_object: &&'static T = UnsafeStore::get(&self.items, position)
+        trait_objects
+    }
+
+    pub(crate) fn try_get_all_trait_objects<T: ?Sized + 'static>(
+        &self,
+    ) -> Option<impl Iterator<Item = &T>> {
+        let (injector, positions) = self.lineage().find_map(|injector| {
+            let positions = injector.multi_bindings_index.get(&TypeId::of::<&'static T>())?;
+            Some((injector, positions))
+        })?;
+
+        Some(positions.iter().map(|&(_, position)| {
+            let boxed_trait_object: &&'static T = UnsafeStore::get(&injector.items, position)
                 .unwrap() // any usize in the `index` has to map to an item in the UnsafeStore
                 .downcast_ref()
                 .unwrap(); // We check that the `dyn Any`s match up with what they say they do on insert
@@ -94,10 +231,112 @@ impl Injector {
             // cannot outlive the injector. However, once we return it from this function, it gets given
             // the lifetime of the injector (as that's what's in the function signature).
             *boxed_trait_object
+        }))
+    }
+
+    /// Fetch a trait object, like [`Self::get_trait_object`], but for a `T` that may have a
+    /// lifetime bound such as `dyn Trait + 'a`. This is how the arguments of
+    /// `#[inject(with = ...)]` functions get their trait objects, see
+    /// [`crate::derive_api::ResolveTraitObject`]. This will panic if no binding has been made to
+    /// that trait.
+    pub(crate) fn get_binding<T: ?Sized>(&self) -> &T {
+        let Some(item) = self.find(typeid::of::<&T>()) else {
+            panic!(
+                "Unable to get an instance of {} from the injector.",
+                std::any::type_name::<T>()
+            )
+        };
+
+        // SAFETY: Bindings are stored as the `&'static T` of their trait object, under the type ID
+        // of that, so this is that reference. As in `get_trait_object`, it is then given the
+        // lifetime of the injector.
+        unsafe { *(item as *const dyn Any).cast::<&T>() }
+    }
+
+    /// Checks that everything `meta` depends on is in this injector or one of its ancestors, so
+    /// that a missing dependency is an error rather than a panic in the constructor.
+    pub(super) fn check_dependencies(&self, meta: &InjectMeta) -> Result<(), BuildError> {
+        let missing = meta.dependencies.iter().find(|&&dependency| {
+            !meta.collections.contains(&dependency) && self.find(dependency).is_none()
+        });
+        let Some(&missing) = missing else {
+            return Ok(());
+        };
+
+        let dependency = INJECTION_REGISTRY
+            .iter()
+            .map(|create_meta| create_meta())
+            .find(|meta| meta.this == missing || meta.also_creates.contains(&missing))
+            .map(|meta| meta.name)
+            .or_else(|| {
+                BINDING_REGISTRY
+                    .iter()
+                    .map(|create_binding| create_binding())
+                    .find(|binding| binding.trait_object == missing)
+                    .map(|binding| binding.name)
+            })
+            .or_else(|| {
+                REQUEST_SCOPED_REGISTRY
+                    .iter()
+                    .map(|scope_meta| scope_meta())
+                    .find(|scope_meta| scope_meta.this == missing)
+                    .map(|scope_meta| scope_meta.name)
+            });
+        Err(BuildError::MissingDependency {
+            component: meta.name,
+            dependency,
         })
     }
 
-    pub(super) fn build_and_store(&mut self, metadata: &InjectMeta) {
+    /// Whether this injector holds `key` itself, rather than getting it from its parent.
+    pub(super) fn has_own(&self, key: TypeId) -> bool {
+        self.index.contains_key(&key)
+    }
+
+    /// Finds the item stored under `key`, in this injector or the closest ancestor that has one.
+    fn find(&self, key: TypeId) -> Option<&dyn Any> {
+        self.lineage().find_map(|injector| {
+            let &position = injector.index.get(&key)?;
+            // any usize in the `index` has to map to an item in the UnsafeStore
+            Some(UnsafeStore::get(&injector.items, position).unwrap())
+        })
+    }
+
+    /// This injector, followed by its parent, its parent's parent and so on.
+    fn lineage(&self) -> impl Iterator<Item = &Injector> {
+        std::iter::successors(Some(self), |injector| injector.parent)
+    }
+
+    /// Rebuild every [`crate::Reloadable`] component whose watched file has changed since the last
+    /// check, returning the names of the ones that were rebuilt. Call this periodically to pick up
+    /// changes. An `Injector` can't be shared between threads, so this has to happen on the thread
+    /// that owns it, for example between jobs in its main loop, rather than from a timer thread.
+    ///
+    /// If a component cannot be rebuilt, it keeps its previous value and the error is returned once
+    /// every other component has been checked. It will be retried when its file next changes.
+    pub fn reload_changed(&self) -> Result<Vec<&'static str>, BuildError> {
+        let mut reloaded = Vec::new();
+        let mut first_error = None;
+        for reloadable in &self.reloadables {
+            match reloadable.reload_if_changed(self) {
+                Ok(true) => reloaded.push(reloadable.name()),
+                Ok(false) => {}
+                Err(error) => {
+                    first_error.get_or_insert(BuildError::Component {
+                        component: reloadable.name(),
+                        error,
+                    });
+                }
+            }
+        }
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(reloaded),
+        }
+    }
+
+    pub(super) fn build_and_store(&mut self, metadata: &InjectMeta) -> Result<(), BuildError> {
         let static_item = unsafe {
             // SAFETY: The item returned by metadata.create is super unsafe, because the type system
             // does not know that it cannot outlive its dependencies.
@@ -107,29 +346,99 @@ impl Injector {
             //    it will take care of that for us.
             // 2. Outside the injector: the static item cannot outlive the injector. When we get
             //    it out of the UnsafeStore, we must downcast it before returning it anywhere.
-            (metadata.create)(&self)
+            (metadata.create)(self)
+        }
+        .map_err(|error| BuildError::Component {
+            component: metadata.name,
+            error,
+        })?;
+
+        let static_item = match static_item.downcast::<Reloading>() {
+            Ok(reloading) => {
+                let Reloading(reloadable) = *reloading;
+                let reloadable: Arc<dyn Reload> = reloadable.into();
+                // SAFETY: This points to the heap allocation of the Arc, which we are about to move
+                // into the UnsafeStore, so it stays valid for as long as the injector does. We only
+                // ever use it through `&self`, so it cannot outlive the injector either.
+                let reload = unsafe { &*(&*reloadable as *const dyn Reload) };
+                self.reloadables.push(reload);
+                self.check_and_store_arc(metadata, metadata.this, reloadable);
+                return Ok(());
+            }
+            Err(static_item) => static_item,
+        };
+        let static_item = match static_item.downcast::<Shared>() {
+            Ok(shared) => {
+                let Shared { trait_object, arc } = *shared;
+                if !metadata.is_multi_binding {
+                    let position = UnsafeStore::push(&mut self.items, arc);
+                    self.shared_index.insert(metadata.this, position);
+                }
+                trait_object
+            }
+            Err(static_item) => {
+                // A decorator that borrows hides the shared binding beneath it
+                if !metadata.is_multi_binding {
+                    self.shared_index.remove(&metadata.this);
+                }
+                static_item
+            }
         };
 
+        if metadata.also_creates.is_empty() {
+            self.check_and_store(metadata, metadata.this, static_item);
+            return Ok(());
+        }
+
+        let Ok(components) = static_item.downcast::<Components>() else {
+            panic!(
+                "Incorrect type returned by the Injectable s constructor for {}",
+                metadata.name
+            )
+        };
+        let expected = std::iter::once(metadata.this).chain(metadata.also_creates.iter().copied());
+        assert_eq!(
+            components.0.len(),
+            metadata.also_creates.len() + 1,
+            "Incorrect number of components returned by the constructor for {}",
+            metadata.name
+        );
+        for (this, static_item) in expected.zip(components.0) {
+            self.check_and_store(metadata, this, static_item);
+        }
+        Ok(())
+    }
+
+    fn check_and_store(&mut self, metadata: &InjectMeta, this: TypeId, static_item: Box<dyn Any>) {
+        self.check_and_store_arc(metadata, this, static_item.into());
+    }
+
+    fn check_and_store_arc(
+        &mut self,
+        metadata: &InjectMeta,
+        this: TypeId,
+        static_item: Arc<dyn Any>,
+    ) {
         assert_eq!(
             static_item.as_ref().type_id(),
-            metadata.this,
+            this,
             "Incorrect type returned by the Injectable s constructor for {}",
             metadata.name
         );
 
-        let position = UnsafeStore::push(&mut self.items, static_item);
+        let position = UnsafeStore::push_arc(&mut self.items, static_item);
         if metadata.is_multi_binding {
-            self.multi_bindings_index
-                .entry(metadata.this)
-                .or_insert_with(Vec::new)
-                .push(position)
+            let positions = self.multi_bindings_index.entry(this).or_default();
+            let after = positions.partition_point(|&(order, _)| order <= metadata.order);
+            positions.insert(after, (metadata.order, position));
         } else {
-            self.index.insert(metadata.this, position);
+            self.index.insert(this, position);
         }
     }
 
-    pub(super) fn store<I: InjectableStatic>(&mut self, static_item: I) {
+    pub(super) fn store<I: Any>(&mut self, static_item: I) {
         let position = UnsafeStore::push(&mut self.items, Box::new(static_item));
         self.index.insert(TypeId::of::<I>(), position);
     }
 }
+
@@ -1,6 +1,15 @@
 mod builder;
+mod child;
+mod error;
 mod injector;
+mod reloadable;
+mod request;
 mod unsafe_storage;
 
 pub use builder::InjectorBuilder;
+pub use child::{ChildInjector, ChildInjectorBuilder};
+pub use error::{BuildError, ComponentError};
 pub use injector::Injector;
+pub use reloadable::{Reloadable, Snapshot};
+pub use request::{InRequestScope, RequestScope, RequestScopeBuilder};
+pub(crate) use reloadable::Reload;
@@ -0,0 +1,179 @@
+use std::{
+    any::Any,
+    fs,
+    marker::PhantomData,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::SystemTime,
+};
+
+use super::{ComponentError, Injector};
+use crate::{Injectable, derive_api::InjectableStatic};
+
+/// A component that is rebuilt whenever a file it watches changes. Types marked with
+/// `#[reloadable(watch = "...")]` are only available through this wrapper, so depend on
+/// `&'a Reloadable<'a, T>` rather than `&'a T`, and call [`Self::get`] whenever you need the
+/// current value.
+///
+/// Changes are picked up by [`Injector::reload_changed`], which re-runs the constructor of every
+/// reloadable component whose file has been modified, and swaps the new value in. Nothing else is
+/// rebuilt, so components that depend on a reloadable one keep working with the same wrapper.
+pub struct Reloadable<'a, T> {
+    // This always holds the `InjectableStatic` version of `T`. Storing it as `dyn Any` keeps the
+    // wrapper covariant in `T`, which every component needs to be, where a lock over `T` would not.
+    current: RwLock<Arc<dyn Any>>,
+    watched: PathBuf,
+    last_modified: Mutex<Option<SystemTime>>,
+    rebuild: unsafe fn(&Injector) -> Result<Box<dyn Any>, ComponentError>,
+    lifetime: PhantomData<&'a T>,
+}
+
+// SAFETY: The only thing stopping these from being derived is the `dyn Any`, which is always the
+// static version of `T`, and that is `Send` and `Sync` exactly when `T` is.
+unsafe impl<T: Send + Sync> Send for Reloadable<'_, T> {}
+unsafe impl<T: Send + Sync> Sync for Reloadable<'_, T> {}
+
+/// The value of a [`Reloadable`] component at the time [`Reloadable::get`] was called. Reloads
+/// don't affect a snapshot that has already been taken, so hold onto it for as long as you need a
+/// consistent view, and call [`Reloadable::get`] again to see any changes.
+pub struct Snapshot<T> {
+    inner: Arc<T>,
+}
+
+impl<'a, T: Injectable<'a>> Reloadable<'a, T> {
+    /// Get the current value of the component.
+    pub fn get(&self) -> Snapshot<T> {
+        let current = Arc::clone(&self.current.read().unwrap());
+        assert!(
+            current.is::<T::Static>(),
+            "Reloadable components are only ever replaced with the same type"
+        );
+        // SAFETY: We just checked that this is a `T::Static`, and `T` is the same type apart from
+        // its lifetimes, so this is the same as the downcast that every other component goes
+        // through before being handed out.
+        let inner = unsafe { Arc::from_raw(Arc::into_raw(current).cast::<T>()) };
+        Snapshot { inner }
+    }
+
+    /// The file that this component is rebuilt from.
+    pub fn watched(&self) -> &Path {
+        &self.watched
+    }
+}
+
+impl<S: InjectableStatic> Reloadable<'static, S> {
+    pub(crate) fn new(
+        injector: &Injector,
+        watched: PathBuf,
+        rebuild: unsafe fn(&Injector) -> Result<Box<dyn Any>, ComponentError>,
+    ) -> Result<Self, ComponentError> {
+        // Read the modification time first, so that a change made while we are building is not
+        // missed by the next reload.
+        let last_modified = Mutex::new(modified(&watched));
+        let current = RwLock::new(Arc::new(Self::build(injector, rebuild)?) as Arc<dyn Any>);
+
+        Ok(Reloadable {
+            current,
+            watched,
+            last_modified,
+            rebuild,
+            lifetime: PhantomData,
+        })
+    }
+
+    fn build(
+        injector: &Injector,
+        rebuild: unsafe fn(&Injector) -> Result<Box<dyn Any>, ComponentError>,
+    ) -> Result<S, ComponentError> {
+        // SAFETY: See the docs for `InjectMeta::create`. The rebuilt value only borrows the
+        // dependencies of this component, which were created before it and so outlive it (and any
+        // value it hands out).
+        let static_item = unsafe { rebuild(injector) }?;
+        let Ok(static_item) = static_item.downcast::<S>() else {
+            panic!(
+                "Incorrect type returned by the Injectable s constructor for {}",
+                std::any::type_name::<S>()
+            )
+        };
+
+        Ok(*static_item)
+    }
+}
+
+/// The part of a [`Reloadable`] that the injector needs, without knowing what type it holds.
+pub(crate) trait Reload: Any {
+    fn name(&self) -> &'static str;
+
+    /// Rebuild the component if its file has changed since the last time we checked, returning
+    /// whether it was rebuilt.
+    fn reload_if_changed(&self, injector: &Injector) -> Result<bool, ComponentError>;
+}
+
+impl<S: InjectableStatic> Reload for Reloadable<'static, S> {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<S>()
+    }
+
+    fn reload_if_changed(&self, injector: &Injector) -> Result<bool, ComponentError> {
+        let modified = modified(&self.watched);
+        {
+            let mut last_modified = self.last_modified.lock().unwrap();
+            if *last_modified == modified {
+                return Ok(false);
+            }
+            // If the rebuild fails we keep the old value, and try again on the next change
+            *last_modified = modified;
+        }
+
+        let fresh = Arc::new(Self::build(injector, self.rebuild)?);
+        *self.current.write().unwrap() = fresh;
+        Ok(true)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+impl<T> Deref for Snapshot<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> Clone for Snapshot<T> {
+    fn clone(&self) -> Self {
+        Snapshot {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<'a, T: Injectable<'a>> Injectable<'a> for Reloadable<'a, T> {
+    type Static = Reloadable<'static, T::Static>;
+
+    unsafe fn upcast(self) -> Self::Static {
+        Reloadable {
+            current: self.current,
+            watched: self.watched,
+            last_modified: self.last_modified,
+            rebuild: self.rebuild,
+            lifetime: PhantomData,
+        }
+    }
+}
+
+impl<S: InjectableStatic> InjectableStatic for Reloadable<'static, S> {
+    type Injectable<'a> = Reloadable<'a, S::Injectable<'a>>;
+
+    fn downcast(&self) -> &Self::Injectable<'_> {
+        // SAFETY: The two types only differ by lifetimes, so this is the same as the transmute that
+        // the derive macro generates for upcast, but in the other direction.
+        unsafe { &*(self as *const Self).cast() }
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    ptr::NonNull,
+    sync::OnceLock,
+    task::{Context, Poll},
+};
+
+use super::{BuildError, Injector};
+use crate::{
+    Injectable,
+    derive_api::{INJECTION_REGISTRY, InjectMeta, InjectableStatic, REQUEST_SCOPED_REGISTRY},
+};
+
+thread_local! {
+    // The scope that `RequestScope::with_current` hands out, see `RequestScope::enter`
+    static CURRENT: Cell<Option<NonNull<RequestScope<'static>>>> = const { Cell::new(None) };
+}
+
+/// A builder for a [`RequestScope`], from [`Injector::request_scope`]. Add the data for the
+/// request with [`Self::inject_value`], then call [`Self::build`].
+pub struct RequestScopeBuilder<'p> {
+    injector: Injector,
+    parent: &'p Injector,
+}
+
+/// The components for a single request. Anything marked `#[injectable(scope = request)]` is built
+/// the first time it is needed, from the values given to the [`RequestScopeBuilder`], the other
+/// request scoped components, and the singletons in the parent [`Injector`]. Everything else comes
+/// straight from the parent, so creating a scope costs next to nothing until it is used.
+///
+/// Dropping the scope drops its components, in the reverse of the order they were created in.
+pub struct RequestScope<'p> {
+    // Components are only ever added to this, see `Self::get` for why references to them stay valid
+    injector: RefCell<Injector>,
+    parent: &'p Injector,
+}
+
+/// A future that runs inside a [`RequestScope`], from [`RequestScope::in_scope`].
+pub struct InRequestScope<'s, F> {
+    scope: &'s RequestScope<'s>,
+    future: F,
+}
+
+/// The constructors for every request scoped component. These are gathered once, and then shared
+/// by every scope.
+struct RequestScopedMetas {
+    metas: Vec<InjectMeta>,
+    // Every component the constructors create, mapped to the position of its constructor
+    positions: HashMap<TypeId, usize>,
+}
+
+impl<'p> RequestScopeBuilder<'p> {
+    pub(super) fn new(parent: &'p Injector) -> Self {
+        RequestScopeBuilder {
+            // SAFETY: The child ends up in a `RequestScope<'p>`, which cannot outlive the parent.
+            injector: unsafe { Injector::empty_child(parent) },
+            parent,
+        }
+    }
+
+    /// Add a value for this request, like [`crate::InjectorBuilder::inject_value`]. This takes
+    /// precedence over any value of the same type in the parent.
+    pub fn inject_value<I>(mut self, value: I) -> Self
+    where
+        I: for<'a> Injectable<'a> + InjectableStatic,
+    {
+        self.injector.store(value);
+        self
+    }
+
+    /// Add a foreign value for this request, like [`crate::InjectorBuilder::inject_foreign`]. This
+    /// takes precedence over any value of the same type in the parent.
+    pub fn inject_foreign<T>(mut self, value: T) -> Self
+    where
+        T: Any,
+    {
+        self.injector.store(value);
+        self
+    }
+
+    /// Finish the scope. Nothing is built until it is first asked for.
+    pub fn build(self) -> RequestScope<'p> {
+        RequestScope {
+            injector: RefCell::new(self.injector),
+            parent: self.parent,
+        }
+    }
+}
+
+impl RequestScope<'_> {
+    /// Fetch an item for this request, building it and any request scoped components it depends on
+    /// if this is the first time they are needed. This will panic if any of those cannot be built,
+    /// see [`Self::try_get`] for a version that doesn't.
+    pub fn get<'a, I: Injectable<'a>>(&'a self) -> &'a I {
+        self.try_get()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like [`Self::get`], but returns an error if the item or anything it depends on cannot be
+    /// built.
+    pub fn try_get<'a, I: Injectable<'a>>(&'a self) -> Result<&'a I, BuildError> {
+        self.build(TypeId::of::<I::Static>(), &mut Vec::new())?;
+
+        let static_item: *const I::Static = self
+            .injector
+            .borrow()
+            .try_get_foreign()
+            .ok_or(BuildError::MissingComponent {
+                component: std::any::type_name::<I::Static>(),
+            })?;
+        // SAFETY: Components live in the heap allocations made for them by the injector's
+        // UnsafeStore, rather than in the injector itself, and those are never moved or dropped
+        // until the scope is. So this stays valid for as long as `self`, even when the injector is
+        // borrowed mutably to build more components.
+        let static_item: &'a I::Static = unsafe { &*static_item };
+        Ok(static_item.downcast())
+    }
+
+    /// Fetch a foreign type for this request, see [`Injector::get_foreign`].
+    pub fn get_foreign<T: Any>(&self) -> &T {
+        let item: *const T = self.injector.borrow().get_foreign();
+        // SAFETY: See `Self::try_get`.
+        unsafe { &*item }
+    }
+
+    /// Fetch a trait object from the parent, see [`Injector::get_trait_object`]. Request scoped
+    /// types cannot be bound, so every binding is a singleton.
+    pub fn get_trait_object<T: ?Sized + 'static>(&self) -> &T {
+        self.parent.get_trait_object()
+    }
+
+    /// Fetch all trait objects implementing a given trait from the parent, see
+    /// [`Injector::get_all_trait_objects`].
+    pub fn get_all_trait_objects<T: ?Sized + 'static>(&self) -> impl Iterator<Item = &T> {
+        self.parent.get_all_trait_objects()
+    }
+
+    /// Run `f` with this as the current scope, so that [`RequestScope::with_current`] can reach it
+    /// from code that has no way to be handed the scope.
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<NonNull<RequestScope<'static>>>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.set(self.0);
+            }
+        }
+
+        let _restore = Restore(CURRENT.replace(Some(NonNull::from(self).cast())));
+        f()
+    }
+
+    /// Wrap a future so that this is the current scope whenever it is polled, like [`Self::enter`].
+    ///
+    /// A scope can't be sent between threads, so neither can this future. That means it can't be
+    /// given to a work stealing spawn like `tokio::spawn`. Await it from the task that owns the
+    /// scope instead, or spawn it onto a single threaded executor, like a `tokio::task::LocalSet`.
+    pub fn in_scope<F: Future>(&self, future: F) -> InRequestScope<'_, F> {
+        InRequestScope {
+            scope: self,
+            future,
+        }
+    }
+
+    /// Call `f` with the current scope, from [`Self::enter`] or [`Self::in_scope`]. This will panic
+    /// if there isn't one, see [`Self::try_with_current`] for a version that doesn't.
+    pub fn with_current<R>(f: impl for<'s> FnOnce(&'s RequestScope<'s>) -> R) -> R {
+        Self::try_with_current(f).expect("There is no current request scope")
+    }
+
+    /// Like [`Self::with_current`], but returns `None` if there is no current scope.
+    pub fn try_with_current<R>(f: impl for<'s> FnOnce(&'s RequestScope<'s>) -> R) -> Option<R> {
+        let current = CURRENT.get()?;
+        // SAFETY: `enter` only makes a scope current while it is borrowed, and puts the previous
+        // one back before that borrow ends. `f` can't keep hold of it, as the lifetime it is given
+        // can't appear in `R`.
+        Some(f(unsafe { current.as_ref() }))
+    }
+
+    /// Whether fetching `key` would run a constructor, rather than find something already built.
+    #[cfg(feature = "tower")]
+    pub(crate) fn needs_building(&self, key: TypeId) -> bool {
+        RequestScopedMetas::get().positions.contains_key(&key)
+            && !self.injector.borrow().has_own(key)
+    }
+
+    /// Adds a foreign value after the scope has been built, like
+    /// [`RequestScopeBuilder::inject_foreign`].
+    #[cfg(feature = "tower")]
+    pub(crate) fn add_foreign<T: Any>(&self, value: T) {
+        self.injector.borrow_mut().store(value);
+    }
+
+    /// Builds the request scoped component `key`, after any request scoped components it depends
+    /// on, unless this scope already has it. Anything else is left to the parent. `in_progress`
+    /// holds the components that are waiting on this one, to catch cycles.
+    fn build(
+        &self,
+        key: TypeId,
+        in_progress: &mut Vec<&'static InjectMeta>,
+    ) -> Result<(), BuildError> {
+        let metas = RequestScopedMetas::get();
+        let Some(&position) = metas.positions.get(&key) else {
+            return Ok(());
+        };
+        let meta = &metas.metas[position];
+        if self.injector.borrow().has_own(meta.this) {
+            return Ok(());
+        }
+        if let Some(start) = in_progress.iter().position(|waiting| std::ptr::eq(*waiting, meta)) {
+            let cycle = in_progress[start..]
+                .iter()
+                .chain([&meta])
+                .map(|waiting| waiting.name)
+                .collect();
+            return Err(BuildError::DependencyCycle { cycle });
+        }
+
+        in_progress.push(meta);
+        for &dependency in &meta.dependencies {
+            self.build(dependency, in_progress)?;
+        }
+        in_progress.pop();
+
+        self.injector.borrow().check_dependencies(meta)?;
+        self.injector.borrow_mut().build_and_store(meta)
+    }
+}
+
+impl<F: Future> Future for InRequestScope<'_, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // SAFETY: The future is pinned along with `self`, and is never moved out of it.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        this.scope.enter(|| future.poll(cx))
+    }
+}
+
+impl RequestScopedMetas {
+    fn get() -> &'static Self {
+        static METAS: OnceLock<RequestScopedMetas> = OnceLock::new();
+
+        METAS.get_or_init(|| {
+            let request_scoped = REQUEST_SCOPED_REGISTRY
+                .iter()
+                .map(|scope_meta| scope_meta().this)
+                .collect::<HashSet<_>>();
+            let metas = INJECTION_REGISTRY
+                .iter()
+                .map(|create_meta| create_meta())
+                .filter(|meta| request_scoped.contains(&meta.this))
+                .collect::<Vec<_>>();
+            let positions = metas
+                .iter()
+                .enumerate()
+                .flat_map(|(position, meta)| {
+                    std::iter::once(meta.this)
+                        .chain(meta.also_creates.iter().copied())
+                        .map(move |this| (this, position))
+                })
+                .collect();
+
+            RequestScopedMetas { metas, positions }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linkme::distributed_slice;
+
+    use super::*;
+    use crate::{ComponentError, derive_api::ScopeMeta};
+
+    struct Chicken;
+    struct Egg;
+
+    unsafe fn unreachable_create(_: &Injector) -> Result<Box<dyn Any>, ComponentError> {
+        unreachable!()
+    }
+
+    fn meta<T: Any, D: Any>() -> InjectMeta {
+        InjectMeta {
+            this: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+            also_creates: Vec::new(),
+            dependencies: vec![TypeId::of::<D>()],
+            collections: Vec::new(),
+            create: unreachable_create,
+            is_multi_binding: false,
+            order: 0,
+        }
+    }
+
+    fn scope_meta<T: Any>() -> ScopeMeta {
+        ScopeMeta {
+            this: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+            is_send: true,
+        }
+    }
+
+    #[distributed_slice(INJECTION_REGISTRY)]
+    fn chicken() -> InjectMeta {
+        meta::<Chicken, Egg>()
+    }
+
+    #[distributed_slice(INJECTION_REGISTRY)]
+    fn egg() -> InjectMeta {
+        meta::<Egg, Chicken>()
+    }
+
+    #[distributed_slice(REQUEST_SCOPED_REGISTRY)]
+    fn chicken_scope() -> ScopeMeta {
+        scope_meta::<Chicken>()
+    }
+
+    #[distributed_slice(REQUEST_SCOPED_REGISTRY)]
+    fn egg_scope() -> ScopeMeta {
+        scope_meta::<Egg>()
+    }
+
+    #[derive(Injectable)]
+    #[has_constructor]
+    struct RequestId(u32);
+
+    #[derive(Injectable)]
+    #[injectable(scope = request)]
+    struct Handler<'a> {
+        id: &'a RequestId,
+    }
+
+    #[test]
+    fn request_scoped_components_are_built_once_per_scope() {
+        let injector = Injector::new();
+        let first = injector.request_scope().inject_value(RequestId(1)).build();
+        let second = injector.request_scope().inject_value(RequestId(2)).build();
+
+        let handler: &Handler = first.get();
+        assert!(std::ptr::eq(handler, first.get::<Handler>()));
+        assert_eq!(handler.id.0, 1);
+        let id = second.enter(|| RequestScope::with_current(|scope| scope.get::<Handler>().id.0));
+        assert_eq!(id, 2);
+        assert!(RequestScope::try_with_current(|_| ()).is_none());
+    }
+
+    #[test]
+    fn cycles_are_an_error() {
+        let injector = Injector::new();
+        let scope = injector.request_scope().build();
+
+        let error = scope.build(TypeId::of::<Chicken>(), &mut Vec::new()).unwrap_err();
+        let BuildError::DependencyCycle { cycle } = error else {
+            panic!("Unexpected error {}", error)
+        };
+        assert_eq!(
+            cycle,
+            [
+                std::any::type_name::<Chicken>(),
+                std::any::type_name::<Egg>(),
+                std::any::type_name::<Chicken>(),
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::any::Any;
+use std::{any::Any, sync::Arc};
 
 /// A data structure for soundly holding onto a list of objects with intrusive pointers between
 /// them.
@@ -13,17 +13,21 @@ use std::any::Any;
 /// **We allow** any item added with [`Self::push`] to hold references to temporary values **if and
 /// only if**:
 /// - Those temporary values were returned by a call to [`Self::get`] on the same `UnsafeStore` that
-///     the item is being pushed onto.
+///   the item is being pushed onto.
 /// - Those temporary values were returned by a call to [`Self::get`] **before** we pushed that item
-///     onto the `UnsafeStore` (or more specifically, that they were pushed onto the `UnsafeStore`
-///     before this item).
+///   onto the `UnsafeStore` (or more specifically, that they were pushed onto the `UnsafeStore`
+///   before this item).
+///
+/// **We also allow** items to be shared beyond the `UnsafeStore` with [`Self::get_arc`] **if and
+/// only if** they do not hold references to any temporary values at all. Such an item may outlive the
+/// store, so it must not borrow from it, but nothing in the store can borrow from it either.
 ///
 /// # Invariants
 /// 1. Items earlier in the list must outlive items later in the list.
 /// 2. References handed out by [`Self::get`] must be stable (there can be no [`Self::get_mut`] API,
-///     and we must ensure that the pointers we hand out remain valid even when the `Vec` resizes).
+///    and we must ensure that the pointers we hand out remain valid even when the `Vec` resizes).
 pub struct UnsafeStore {
-    items: Vec<Box<dyn Any>>
+    items: Vec<Arc<dyn Any>>
 }
 
 impl UnsafeStore {
@@ -34,13 +38,23 @@ impl UnsafeStore {
     }
 
     pub fn get(store: &Self, item: usize) -> Option<&dyn Any> {
-        // Invariant 2: we hand out a reference to the memory allocated by the Box itself, rather
+        // Invariant 2: we hand out a reference to the memory allocated by the Arc itself, rather
         // than a reference to memory allocated by the Vec. This way, calls to push (which may
         // resize the vec) cannot invalidate our pointers.
         store.items.get(item).as_ref().map(|x| &***x)
     }
 
+    pub fn get_arc(store: &Self, item: usize) -> Option<Arc<dyn Any>> {
+        store.items.get(item).cloned()
+    }
+
+    /// The item is moved into an `Arc` here, so only take references to it from [`Self::get`] once
+    /// it has been pushed.
     pub fn push(store: &mut Self, item: Box<dyn Any>) -> usize {
+        Self::push_arc(store, item.into())
+    }
+
+    pub fn push_arc(store: &mut Self, item: Arc<dyn Any>) -> usize {
         let output = store.items.len();
         store.items.push(item);
         output
@@ -67,6 +81,7 @@ mod tests {
         sender: mpsc::Sender<usize>,
     }
 
+    #[allow(clippy::new_ret_no_self)]
     impl DropObserver {
         fn new(id: usize, sender: &mpsc::Sender<usize>) -> Box<dyn Any> {
             Box::new(DropObserver { id, sender: sender.clone() })
@@ -118,4 +133,14 @@ mod tests {
         };
         assert_eq!(reference.downcast_ref::<i32>(), Some(&42i32));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn shared_items_outlive_the_store() {
+        let mut store = UnsafeStore::new();
+        let index = UnsafeStore::push(&mut store, Box::new(String::from("shared")));
+        let shared = UnsafeStore::get_arc(&store, index).unwrap();
+        drop(store);
+
+        assert_eq!(shared.downcast_ref::<String>().map(String::as_str), Some("shared"));
+    }
+}
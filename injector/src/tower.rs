@@ -0,0 +1,499 @@
+//! A [tower](https://docs.rs/tower) [`Layer`] that gives every HTTP request its own
+//! [`RequestScope`] of the [`global`] injector, which must be installed with [`global::install`]
+//! first. Handlers then fetch request scoped components (and singletons) from the request with
+//! [`Inject`].
+//!
+//! The first time anything is fetched with [`Inject::from_request`], the scope is given the
+//! request's [`http::Method`], [`http::Uri`] and [`http::HeaderMap`] as foreign values, so a
+//! request scoped constructor can take `&HeaderMap` to work out who is calling, for example.
+//! Requests that never fetch anything don't pay for copying them.
+//!
+//! Requests can be handled on any thread, so as with the global injector, only components that are
+//! `Sync` can be fetched. Every request scoped type must also be `Send`, as a scope is dropped on
+//! whichever thread is last to finish with it. The constructors and destructors of request scoped
+//! components run one at a time, so they can still use any of the singletons, but they must not
+//! use [`Inject`] themselves, as that would wait on them to finish.
+
+use std::{
+    error::Error,
+    fmt,
+    mem::ManuallyDrop,
+    ops::Deref,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll},
+};
+
+use http::{Extensions, HeaderMap, Method, Request, Uri};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+    BuildError, Owned, RequestScope,
+    derive_api::{InjectableStatic, REQUEST_SCOPED_REGISTRY, ScopeMeta},
+    global,
+};
+
+/// Held while any request scoped constructor or destructor runs, as those can use singletons that
+/// aren't `Sync`. It is always taken before the lock of a scope.
+static CONSTRUCTING: Mutex<()> = Mutex::new(());
+
+/// Adds a [`RequestScope`] to every request, see the [module docs](self).
+#[derive(Clone, Copy)]
+pub struct InjectorLayer {
+    // Only made by `try_new`, once the global injector is known to be installed
+    _installed: (),
+}
+
+/// The [`Service`] made by [`InjectorLayer`].
+#[derive(Clone)]
+pub struct InjectorService<S> {
+    inner: S,
+}
+
+/// A component fetched from the [`RequestScope`] of a request. It keeps the scope alive, so it can
+/// be held onto for as long as the handler needs.
+///
+/// For types without a lifetime, this derefs to the component. For others, name the type with
+/// `'static` lifetimes, like `Inject<Orders<'static>>`, and use [`Self::get`].
+pub struct Inject<T: 'static> {
+    scope: Arc<SharedScope>,
+    // Really borrowed from `scope`, so this is never handed out as `'static`
+    item: &'static T,
+}
+
+/// An error fetching a component with [`Inject`].
+#[derive(Debug)]
+pub enum InjectError {
+    /// The request did not go through an [`InjectorLayer`].
+    MissingLayer,
+    /// The component, or one of the request scoped components it depends on, could not be built.
+    Build(BuildError),
+}
+
+/// An error creating an [`InjectorLayer`].
+#[derive(Debug)]
+pub enum LayerError {
+    /// The [`global`] injector has not been installed yet.
+    NotInstalled,
+    /// A request scoped type isn't `Send`, so its scopes can't be shared between threads.
+    NotSend {
+        /// The name of the type.
+        component: &'static str,
+    },
+}
+
+/// The scope for one request, which is shared between threads through the request extensions.
+struct SharedScope {
+    state: Mutex<ScopeState>,
+}
+
+struct ScopeState {
+    // Only dropped by `SharedScope`, with `CONSTRUCTING` held
+    scope: ManuallyDrop<RequestScope<'static>>,
+    has_request_parts: bool,
+}
+
+// SAFETY: The scope is only used with the lock in `SharedScope` held, so no two threads use it at
+// the same time. It borrows the global injector, which is fine to share for the reasons given in
+// `global`, and its constructors and destructors can use singletons that aren't `Sync`, so those
+// only run with `CONSTRUCTING` held as well. Everything the scope owns is `Send`, which
+// `InjectorLayer::try_new` checks, so it can be dropped on any thread.
+unsafe impl Send for ScopeState {}
+
+impl InjectorLayer {
+    /// Create the layer, which makes its scopes from the [`global`] injector.
+    ///
+    /// This will panic if the global injector isn't installed, or any request scoped type isn't
+    /// `Send`, use [`Self::try_new`] to handle that instead.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like [`Self::new`], but returns an error if the global injector isn't installed, or a
+    /// request scoped type isn't `Send`.
+    pub fn try_new() -> Result<Self, LayerError> {
+        global::installed().map_err(|_| LayerError::NotInstalled)?;
+        check_send(REQUEST_SCOPED_REGISTRY.iter().map(|create_meta| create_meta()))?;
+        Ok(InjectorLayer { _installed: () })
+    }
+}
+
+fn check_send(scope_metas: impl IntoIterator<Item = ScopeMeta>) -> Result<(), LayerError> {
+    match scope_metas.into_iter().find(|scope_meta| !scope_meta.is_send) {
+        Some(scope_meta) => Err(LayerError::NotSend {
+            component: scope_meta.name,
+        }),
+        None => Ok(()),
+    }
+}
+
+impl<S> Layer<S> for InjectorLayer {
+    type Service = InjectorService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InjectorService { inner }
+    }
+}
+
+impl<S, B> Service<Request<B>> for InjectorService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        // The layer can only be made once the injector is installed, and it is never uninstalled
+        let injector = global::installed().unwrap_or_else(|error| panic!("{}", error));
+        let state = ScopeState {
+            scope: ManuallyDrop::new(injector.request_scope().build()),
+            has_request_parts: false,
+        };
+        let scope = SharedScope {
+            state: Mutex::new(state),
+        };
+        request.extensions_mut().insert(Arc::new(scope));
+        self.inner.call(request)
+    }
+}
+
+impl<T: InjectableStatic + Sync> Inject<T> {
+    /// Fetch the component from the scope of this request, building it if it is request scoped
+    /// and this is the first time it is needed.
+    pub fn from_request<B>(request: &Request<B>) -> Result<Self, InjectError> {
+        let parts = (request.method(), request.uri(), request.headers());
+        Self::fetch(request.extensions(), Some(parts))
+    }
+
+    /// Like [`Self::from_request`], for when the request has already been taken apart. The scope
+    /// only has the method, URI and headers of the request once something has been fetched with
+    /// [`Self::from_request`].
+    pub fn from_extensions(extensions: &Extensions) -> Result<Self, InjectError> {
+        Self::fetch(extensions, None)
+    }
+
+    fn fetch(
+        extensions: &Extensions,
+        parts: Option<(&Method, &Uri, &HeaderMap)>,
+    ) -> Result<Self, InjectError> {
+        let Some(scope) = extensions.get::<Arc<SharedScope>>() else {
+            return Err(InjectError::MissingLayer);
+        };
+
+        let mut state = lock(&scope.state);
+        if let Some(parts) = parts {
+            state.add_request_parts(parts);
+        }
+        let _constructing = if state.scope.needs_building(std::any::TypeId::of::<T>()) {
+            drop(state);
+            let constructing = lock(&CONSTRUCTING);
+            state = lock(&scope.state);
+            Some(constructing)
+        } else {
+            None
+        };
+
+        let item = state
+            .scope
+            .try_get::<T::Injectable<'_>>()
+            .map_err(InjectError::Build)?;
+        // SAFETY: The scope never moves or drops its components while it is alive, and the `Arc`
+        // we keep holds it alive. The item is only ever lent out for as long as we are borrowed.
+        let item = unsafe { &*(item as *const T::Injectable<'_>).cast::<T>() };
+        Ok(Inject {
+            scope: Arc::clone(scope),
+            item,
+        })
+    }
+}
+
+impl<T: InjectableStatic> Inject<T> {
+    /// The component, with its lifetimes tied to this `Inject`.
+    pub fn get(&self) -> &T::Injectable<'_> {
+        self.item.downcast()
+    }
+}
+
+impl<T: Owned> Deref for Inject<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item
+    }
+}
+
+impl<T> Clone for Inject<T> {
+    fn clone(&self) -> Self {
+        Inject {
+            scope: Arc::clone(&self.scope),
+            item: self.item,
+        }
+    }
+}
+
+impl ScopeState {
+    /// Copies the request's method, URI and headers into the scope, the first time this is called.
+    fn add_request_parts(&mut self, (method, uri, headers): (&Method, &Uri, &HeaderMap)) {
+        if !std::mem::replace(&mut self.has_request_parts, true) {
+            self.scope.add_foreign(method.clone());
+            self.scope.add_foreign(uri.clone());
+            self.scope.add_foreign(headers.clone());
+        }
+    }
+}
+
+impl Drop for SharedScope {
+    fn drop(&mut self) {
+        let _constructing = lock(&CONSTRUCTING);
+        let state = self.state.get_mut().unwrap_or_else(PoisonError::into_inner);
+        // SAFETY: This is the only place the scope is dropped, and it is never used again.
+        unsafe { ManuallyDrop::drop(&mut state.scope) };
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl fmt::Debug for InjectorLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InjectorLayer").finish_non_exhaustive()
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for InjectorService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InjectorService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for InjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InjectError::MissingLayer => write!(
+                f,
+                "the request has no request scope, see injector::tower::InjectorLayer"
+            ),
+            InjectError::Build(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for InjectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InjectError::MissingLayer => None,
+            InjectError::Build(error) => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for LayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerError::NotInstalled => write!(
+                f,
+                "the tower layer needs the global injector, see injector::global::install"
+            ),
+            LayerError::NotSend { component } => write!(
+                f,
+                "{} is request scoped but isn't Send, so the tower layer can't share its scopes between threads",
+                component
+            ),
+        }
+    }
+}
+
+impl Error for LayerError {}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        future::Future,
+        pin::pin,
+        sync::{
+            Once,
+            atomic::{AtomicUsize, Ordering},
+        },
+        task::{Context, Poll, Waker},
+        time::Duration,
+    };
+
+    use http::Response;
+    use tower::{ServiceExt, service_fn};
+
+    use super::*;
+    use crate::{Injectable, Injector, constructor};
+
+    static USERS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+    static VISITS_BUILT: AtomicUsize = AtomicUsize::new(0);
+    static VISITS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Injectable)]
+    #[has_constructor]
+    #[injectable(scope = request)]
+    struct User {
+        name: String,
+    }
+
+    #[constructor]
+    fn user(headers: &HeaderMap) -> User {
+        let name = headers["x-user"].to_str().unwrap();
+        User { name: name.to_string() }
+    }
+
+    impl Drop for User {
+        fn drop(&mut self) {
+            USERS_DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Never given to any injector
+    struct Sessions;
+
+    #[derive(Injectable)]
+    #[has_constructor]
+    #[injectable(scope = request)]
+    struct Session;
+
+    #[constructor]
+    fn session(_: &Sessions) -> Session {
+        Session
+    }
+
+    // Remembers the thread that built it
+    #[derive(Injectable)]
+    #[has_constructor]
+    #[injectable(scope = request)]
+    struct Visit {
+        thread: std::thread::ThreadId,
+    }
+
+    #[constructor]
+    fn visit() -> Visit {
+        // Gives other threads the chance to try building it at the same time
+        std::thread::sleep(Duration::from_millis(10));
+        VISITS_BUILT.fetch_add(1, Ordering::Relaxed);
+        Visit { thread: std::thread::current().id() }
+    }
+
+    impl Drop for Visit {
+        fn drop(&mut self) {
+            VISITS_DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // The global injector can only be installed once, so every test shares it
+    fn layer() -> InjectorLayer {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| global::install(Injector::new()).unwrap());
+        InjectorLayer::new()
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    async fn handler<T: InjectableStatic + Sync>(
+        request: Request<()>,
+    ) -> Result<Response<Result<Inject<T>, InjectError>>, Infallible> {
+        Ok(Response::new(Inject::from_request(&request)))
+    }
+
+    // Hands the whole scope back, for tests to use from other threads
+    async fn extensions(request: Request<()>) -> Result<Response<Extensions>, Infallible> {
+        Ok(Response::new(request.into_parts().0.extensions))
+    }
+
+    #[test]
+    fn requests_without_the_layer_are_an_error() {
+        let response = block_on(service_fn(handler::<User>).oneshot(Request::new(()))).unwrap();
+
+        assert!(matches!(response.into_body(), Err(InjectError::MissingLayer)));
+    }
+
+    #[test]
+    fn build_errors_are_returned() {
+        let service = layer().layer(service_fn(handler::<Session>));
+        let response = block_on(service.oneshot(Request::new(()))).unwrap();
+
+        let Err(InjectError::Build(BuildError::MissingDependency { component, .. })) =
+            response.into_body()
+        else {
+            panic!("Expected a missing dependency")
+        };
+        assert_eq!(component, std::any::type_name::<Session>());
+    }
+
+    #[test]
+    fn scopes_are_dropped_once_nothing_uses_them() {
+        let service = layer().layer(service_fn(handler::<User>));
+        let request = Request::builder().header("x-user", "ana").body(()).unwrap();
+        let user = block_on(service.oneshot(request)).unwrap().into_body().unwrap();
+
+        // The response keeps the scope alive after the handler has finished
+        assert_eq!(user.name, "ana");
+        assert_eq!(USERS_DROPPED.load(Ordering::Relaxed), 0);
+        drop(user);
+        assert_eq!(USERS_DROPPED.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn scopes_can_be_shared_between_threads() {
+        let service = layer().layer(service_fn(extensions));
+        let extensions = block_on(service.oneshot(Request::new(()))).unwrap().into_body();
+
+        // Built once, however many threads fetch it at the same time
+        let visits = std::thread::scope(|scope| {
+            let threads = (0..4)
+                .map(|_| scope.spawn(|| Inject::<Visit>::from_extensions(&extensions).unwrap()))
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(VISITS_BUILT.load(Ordering::Relaxed), 1);
+        assert!(visits.iter().all(|visit| std::ptr::eq(visit.item, visits[0].item)));
+
+        // Each `Inject` is usable from another thread, and the last one drops the scope there
+        let main_thread = std::thread::current().id();
+        std::thread::spawn(move || {
+            assert!(visits.iter().all(|visit| visit.thread != main_thread));
+            drop(extensions);
+            assert_eq!(VISITS_DROPPED.load(Ordering::Relaxed), 0);
+            drop(visits);
+            assert_eq!(VISITS_DROPPED.load(Ordering::Relaxed), 1);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn request_scoped_types_must_be_send() {
+        let scope_meta = |is_send| ScopeMeta {
+            this: std::any::TypeId::of::<User>(),
+            name: "User",
+            is_send,
+        };
+
+        assert!(check_send([scope_meta(true)]).is_ok());
+        let error = check_send([scope_meta(true), scope_meta(false)]).unwrap_err();
+        assert!(matches!(error, LayerError::NotSend { component: "User" }));
+    }
+}
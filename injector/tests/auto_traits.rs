@@ -0,0 +1,32 @@
+use injector::{Injectable, Injector, binding};
+
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+#[derive(Injectable)]
+pub struct FixedClock;
+
+#[binding(bounds = Send + Sync)]
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        42
+    }
+}
+
+#[derive(Injectable)]
+pub struct Scheduler<'a> {
+    // The same type as `dyn Clock + Send + Sync`
+    clock: &'a (dyn Sync + Clock + Send),
+}
+
+#[test]
+fn auto_trait_bounds_can_be_written_in_any_order() {
+    let injector = Injector::new();
+    let clock = injector.get_trait_object::<dyn Clock + Send + Sync>();
+    let scheduler: &Scheduler = injector.get();
+
+    assert!(std::ptr::addr_eq(clock, scheduler.clock));
+    let now = std::thread::scope(|scope| scope.spawn(|| clock.now()).join().unwrap());
+    assert_eq!(now, 42);
+}
@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+
+use injector::{Chain, Injectable, Injector, Next, multi_binding};
+
+pub trait Step {
+    fn run(&self, trace: &RefCell<Vec<&'static str>>, next: Next<'_, dyn Step>);
+}
+
+fn run(next: Next<'_, dyn Step>, trace: &RefCell<Vec<&'static str>>) {
+    match next.pop() {
+        Some((step, next)) => step.run(trace, next),
+        None => trace.borrow_mut().push("end"),
+    }
+}
+
+macro_rules! step {
+    ($name:ident, $order:literal) => {
+        #[derive(Injectable)]
+        pub struct $name;
+
+        #[multi_binding(order = $order)]
+        impl Step for $name {
+            fn run(&self, trace: &RefCell<Vec<&'static str>>, next: Next<'_, dyn Step>) {
+                trace.borrow_mut().push(stringify!($name));
+                run(next, trace);
+            }
+        }
+    };
+}
+
+step!(Last, 5);
+step!(First, -1);
+step!(Middle, 2);
+step!(AlsoMiddle, 2);
+
+#[derive(Injectable)]
+pub struct Pipeline<'a> {
+    #[from_chain(dyn Step)]
+    steps: Chain<'a, dyn Step>,
+}
+
+#[test]
+fn elements_are_popped_in_order() {
+    let injector = Injector::new();
+    let pipeline: &Pipeline = injector.get();
+    assert_eq!(pipeline.steps.len(), 4);
+
+    let trace = RefCell::new(Vec::new());
+    let next = pipeline.steps.start();
+    assert_eq!(next.len(), 4);
+    run(next, &trace);
+
+    // Elements with the same order both run between their neighbours, in either order
+    let mut trace = trace.into_inner();
+    assert_eq!(trace[0], "First");
+    assert_eq!(trace[3..], ["Last", "end"]);
+    trace[1..3].sort();
+    assert_eq!(trace[1..3], ["AlsoMiddle", "Middle"]);
+}
+
+#[test]
+fn popping_the_last_element_leaves_an_empty_handle() {
+    let injector = Injector::new();
+    let pipeline: &Pipeline = injector.get();
+
+    let mut next = pipeline.steps.start();
+    for _ in 0..4 {
+        let (_, rest) = next.pop().unwrap();
+        next = rest;
+    }
+    assert!(next.is_empty());
+    assert!(next.pop().is_none());
+}
@@ -0,0 +1,46 @@
+use injector::{Injectable, Injector, provides};
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Settings {
+    limits: Limits,
+}
+
+struct Limits {
+    requests: u32,
+}
+
+#[provides]
+fn settings() -> Settings {
+    Settings {
+        limits: Limits { requests: 10 },
+    }
+}
+
+#[derive(Injectable, Clone)]
+#[has_constructor]
+struct Retries(u32);
+
+#[provides]
+fn retries() -> Retries {
+    Retries(3)
+}
+
+#[derive(Injectable)]
+struct Client {
+    #[inject(clone)]
+    retries: Retries,
+    #[inject(from = Settings, path = limits.requests, clone)]
+    requests: u32,
+}
+
+#[test]
+fn cloned_fields_are_copies() {
+    let injector = Injector::new();
+    let retries: &Retries = injector.get();
+    let client: &Client = injector.get();
+
+    assert!(!std::ptr::eq(&client.retries, retries));
+    assert_eq!(client.retries.0, 3);
+    assert_eq!(client.requests, 10);
+}
@@ -0,0 +1,29 @@
+use injector::{Injectable, Injector};
+
+pub trait Tagged {
+    fn tag(&self) -> &'static str;
+}
+
+// Only the derived impl of `Debug` is available to bind
+#[derive(Injectable, Debug)]
+#[binds(dyn std::fmt::Debug, multi(dyn Tagged))]
+pub struct Region;
+
+impl Tagged for Region {
+    fn tag(&self) -> &'static str {
+        "region"
+    }
+}
+
+#[test]
+fn derives_can_bind_their_type() {
+    let injector = Injector::new();
+
+    let debug = injector.get_trait_object::<dyn std::fmt::Debug>();
+    assert_eq!(format!("{:?}", debug), "Region");
+    let tags = injector
+        .get_all_trait_objects::<dyn Tagged>()
+        .map(|tagged| tagged.tag())
+        .collect::<Vec<_>>();
+    assert_eq!(tags, ["region"]);
+}
@@ -0,0 +1,39 @@
+use std::env;
+
+use injector::{BuildError, ComponentError, Injectable, Injector};
+
+#[derive(Injectable)]
+struct Server {
+    #[inject(env = "ENV_FIELDS_PORT", default = 8080)]
+    port: u16,
+}
+
+// The environment is shared by the whole process, so this is a single test rather than several
+// that could run in parallel
+#[test]
+fn fields_are_parsed_from_the_environment_or_their_default() {
+    // SAFETY: This is the only test in this binary, so nothing else reads the environment
+    unsafe { env::set_var("ENV_FIELDS_PORT", "not a port") };
+    let error = Injector::builder().try_build_the_world().err().unwrap();
+    let BuildError::Component {
+        component,
+        error:
+            ComponentError::InvalidEnvironmentVariable {
+                variable, value, ..
+            },
+    } = error
+    else {
+        panic!("Unexpected error {}", error)
+    };
+    assert_eq!(component, std::any::type_name::<Server>());
+    assert_eq!(variable, "ENV_FIELDS_PORT");
+    assert_eq!(value, "not a port");
+
+    // SAFETY: As above
+    unsafe { env::remove_var("ENV_FIELDS_PORT") };
+    assert_eq!(Injector::new().get::<Server>().port, 8080);
+
+    // SAFETY: As above
+    unsafe { env::set_var("ENV_FIELDS_PORT", "9000") };
+    assert_eq!(Injector::new().get::<Server>().port, 9000);
+}
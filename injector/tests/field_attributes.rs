@@ -0,0 +1,56 @@
+use injector::{Injectable, Injector, binding, provides};
+
+/// Stands in for a type from another crate, which can't be given an `Injectable` impl here.
+pub struct ThreadPool {
+    threads: usize,
+}
+
+#[provides]
+fn thread_pool() -> ThreadPool {
+    ThreadPool { threads: 4 }
+}
+
+#[derive(Injectable)]
+pub struct Limits<'a> {
+    pool: &'a ThreadPool,
+}
+
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+#[derive(Injectable)]
+pub struct FixedClock;
+
+#[binding]
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        42
+    }
+}
+
+fn describe(pool: &ThreadPool, limits: &Limits, clock: &dyn Clock) -> String {
+    format!("{} of {} threads at {}", pool.threads, limits.pool.threads, clock.now())
+}
+
+#[derive(Injectable)]
+pub struct Worker {
+    #[inject(default)]
+    completed: Vec<u32>,
+    #[inject(with = describe)]
+    description: String,
+}
+
+#[test]
+fn default_fields_start_out_empty() {
+    let injector = Injector::new();
+
+    assert!(injector.get::<Worker>().completed.is_empty());
+}
+
+#[test]
+fn with_functions_are_given_their_arguments() {
+    let injector = Injector::new();
+
+    assert_eq!(injector.get::<Worker>().description, "4 of 4 threads at 42");
+}
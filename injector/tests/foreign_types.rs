@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use injector::{Injectable, Injector, provides};
+
+/// Stands in for a type from another crate, which can't be given an `Injectable` impl here.
+pub struct ThreadPool {
+    threads: usize,
+}
+
+#[provides]
+fn data_dir() -> PathBuf {
+    PathBuf::from("/var/lib/app")
+}
+
+#[provides]
+fn thread_pool() -> ThreadPool {
+    ThreadPool { threads: 4 }
+}
+
+#[derive(Injectable)]
+pub struct Storage<'a> {
+    data_dir: &'a PathBuf,
+    pool: &'a ThreadPool,
+    timeout: &'a Duration,
+}
+
+fn injector() -> Injector {
+    Injector::builder()
+        .inject_foreign(Duration::from_secs(30))
+        .build_the_world()
+}
+
+#[test]
+fn provided_foreign_types_can_be_fetched() {
+    let injector = injector();
+
+    assert_eq!(injector.get::<PathBuf>(), &PathBuf::from("/var/lib/app"));
+    assert_eq!(injector.get_foreign::<ThreadPool>().threads, 4);
+
+    let storage: &Storage = injector.get();
+    assert!(std::ptr::eq(storage.data_dir, injector.get::<PathBuf>()));
+    assert!(std::ptr::eq(storage.pool, injector.get_foreign::<ThreadPool>()));
+}
+
+#[test]
+fn injected_foreign_values_can_be_fetched() {
+    let injector = injector();
+
+    assert_eq!(injector.get::<Duration>(), &Duration::from_secs(30));
+    assert_eq!(*injector.get_arc::<Duration>(), Duration::from_secs(30));
+
+    let storage: &Storage = injector.get();
+    assert!(std::ptr::eq(storage.timeout, injector.get::<Duration>()));
+}
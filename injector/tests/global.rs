@@ -0,0 +1,21 @@
+use injector::{
+    Injectable, Injector,
+    global::{self, GlobalError},
+};
+
+#[derive(Injectable)]
+pub struct Installed;
+
+// The global injector can only be installed once per process, so this is the only test to do so
+#[test]
+fn installing_twice_or_not_at_all_is_an_error() {
+    assert!(!global::is_installed());
+    assert!(matches!(global::try_get::<Installed>(), Err(GlobalError::NotInstalled)));
+
+    global::install(Injector::new()).unwrap();
+    assert!(global::is_installed());
+    assert!(std::ptr::eq(global::try_get::<Installed>().unwrap(), injector::inject!(Installed)));
+
+    let error = global::install(Injector::new()).unwrap_err();
+    assert!(matches!(error, GlobalError::AlreadyInstalled));
+}
@@ -0,0 +1,148 @@
+use std::{cell::RefCell, time::Duration};
+
+use injector::{
+    Injectable, Injector, Interceptor, MethodCall, Rejection, binding, interceptable,
+    multi_binding,
+};
+
+#[interceptable]
+pub trait Accounts {
+    fn balance(&self, account: u32) -> u64;
+    fn withdraw(&self, account: u32, amount: u64) -> Result<u64, AccountError>;
+    fn close(&self, account: u32);
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AccountError {
+    Rejected(Rejection),
+}
+
+impl From<Rejection> for AccountError {
+    fn from(rejection: Rejection) -> Self {
+        AccountError::Rejected(rejection)
+    }
+}
+
+#[derive(Injectable)]
+pub struct Bank {
+    #[inject(default)]
+    calls: RefCell<Vec<String>>,
+}
+
+#[binding(intercept)]
+impl Accounts for Bank {
+    fn balance(&self, account: u32) -> u64 {
+        self.calls.borrow_mut().push(format!("balance {}", account));
+        std::thread::sleep(Duration::from_millis(5));
+        100
+    }
+
+    fn withdraw(&self, account: u32, amount: u64) -> Result<u64, AccountError> {
+        self.calls.borrow_mut().push(format!("withdraw {} {}", account, amount));
+        Ok(100 - amount)
+    }
+
+    fn close(&self, account: u32) {
+        self.calls.borrow_mut().push(format!("close {}", account));
+    }
+}
+
+#[derive(Injectable)]
+pub struct Record {
+    #[inject(default)]
+    events: RefCell<Vec<String>>,
+    #[inject(default)]
+    elapsed: RefCell<Vec<(&'static str, Duration)>>,
+}
+
+#[derive(Injectable)]
+pub struct Outer<'a> {
+    record: &'a Record,
+}
+
+#[multi_binding]
+impl Interceptor for Outer<'_> {
+    fn before(&self, call: &MethodCall) -> Result<(), Rejection> {
+        self.record.events.borrow_mut().push(format!("outer before {}", call.method));
+        Ok(())
+    }
+
+    fn after(&self, call: &MethodCall, elapsed: Duration) {
+        self.record.events.borrow_mut().push(format!("outer after {}", call.method));
+        self.record.elapsed.borrow_mut().push((call.method, elapsed));
+    }
+}
+
+/// Turns away every withdrawal, and closing accounts.
+#[derive(Injectable)]
+pub struct Inner<'a> {
+    record: &'a Record,
+}
+
+#[multi_binding(order = 1)]
+impl Interceptor for Inner<'_> {
+    fn before(&self, call: &MethodCall) -> Result<(), Rejection> {
+        self.record.events.borrow_mut().push(format!("inner before {}", call.method));
+        match call.method {
+            "withdraw" | "close" => Err(Rejection::new(call, "not allowed")),
+            _ => Ok(()),
+        }
+    }
+
+    fn after(&self, call: &MethodCall, _elapsed: Duration) {
+        self.record.events.borrow_mut().push(format!("inner after {}", call.method));
+    }
+}
+
+#[test]
+fn interceptors_run_around_forwarded_calls_in_order() {
+    let injector = Injector::new();
+    let accounts: &dyn Accounts = injector.get_trait_object();
+
+    assert_eq!(accounts.balance(7), 100);
+
+    assert_eq!(*injector.get::<Bank>().calls.borrow(), ["balance 7"]);
+    let record: &Record = injector.get();
+    assert_eq!(
+        *record.events.borrow(),
+        [
+            "outer before balance",
+            "inner before balance",
+            "inner after balance",
+            "outer after balance",
+        ]
+    );
+    let elapsed = record.elapsed.borrow();
+    assert_eq!(elapsed.len(), 1);
+    assert_eq!(elapsed[0].0, "balance");
+    assert!(elapsed[0].1 >= Duration::from_millis(5));
+}
+
+#[test]
+fn rejected_calls_return_the_rejection_as_an_error() {
+    let injector = Injector::new();
+    let accounts: &dyn Accounts = injector.get_trait_object();
+
+    let Err(AccountError::Rejected(rejection)) = accounts.withdraw(7, 20) else {
+        panic!("The withdrawal should have been rejected");
+    };
+    assert_eq!(rejection.call.method, "withdraw");
+    assert!(rejection.call.trait_object.contains("Accounts"));
+    assert_eq!(rejection.reason, "not allowed");
+
+    // Neither the binding nor any `after` is called
+    assert!(injector.get::<Bank>().calls.borrow().is_empty());
+    assert_eq!(
+        *injector.get::<Record>().events.borrow(),
+        ["outer before withdraw", "inner before withdraw"]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Call to close on dyn intercept::Accounts was rejected: not allowed")]
+fn rejected_calls_panic_when_they_cannot_return_an_error() {
+    let injector = Injector::new();
+    let accounts: &dyn Accounts = injector.get_trait_object();
+
+    accounts.close(7);
+}
@@ -0,0 +1,34 @@
+use injector::{Injectable, Injector, provides};
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Settings {
+    limits: Limits,
+}
+
+struct Limits {
+    requests: u32,
+}
+
+#[provides]
+fn settings() -> Settings {
+    Settings {
+        limits: Limits { requests: 10 },
+    }
+}
+
+#[derive(Injectable)]
+struct RateLimiter<'a> {
+    #[inject(from = Settings, path = limits)]
+    limits: &'a Limits,
+}
+
+#[test]
+fn projected_fields_point_into_the_component() {
+    let injector = Injector::new();
+    let settings: &Settings = injector.get();
+    let limiter: &RateLimiter = injector.get();
+
+    assert!(std::ptr::eq(limiter.limits, &settings.limits));
+    assert_eq!(limiter.limits.requests, 10);
+}
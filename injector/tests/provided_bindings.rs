@@ -0,0 +1,60 @@
+use injector::{Injectable, Injector, provides};
+
+trait Greeting {
+    fn greet(&self) -> String;
+}
+
+struct Hello(&'static str);
+
+impl Greeting for Hello {
+    fn greet(&self) -> String {
+        format!("hello {}", self.0)
+    }
+}
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Name(&'static str);
+
+#[provides]
+fn name() -> Name {
+    Name("world")
+}
+
+#[provides]
+fn greeting(name: &Name) -> Box<dyn Greeting> {
+    Box::new(Hello(name.0))
+}
+
+trait Plugin {
+    fn name(&self) -> &'static str;
+}
+
+impl Plugin for Hello {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+#[provides(multi)]
+fn first_plugin() -> Box<dyn Plugin> {
+    Box::new(Hello("first"))
+}
+
+#[provides(multi)]
+fn second_plugin() -> Box<dyn Plugin> {
+    Box::new(Hello("second"))
+}
+
+#[test]
+fn provided_boxes_are_bound_to_their_trait() {
+    let injector = Injector::new();
+
+    assert_eq!(injector.get_trait_object::<dyn Greeting>().greet(), "hello world");
+    let mut plugins = injector
+        .get_all_trait_objects::<dyn Plugin>()
+        .map(|plugin| plugin.name())
+        .collect::<Vec<_>>();
+    plugins.sort();
+    assert_eq!(plugins, ["first", "second"]);
+}
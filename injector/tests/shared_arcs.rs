@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use injector::{Injectable, Injector, binding};
+
+#[derive(Injectable)]
+pub struct Connection;
+
+#[derive(Injectable)]
+pub struct Repository {
+    connection: Arc<Connection>,
+}
+
+pub trait Store {
+    fn connection(&self) -> &Connection;
+}
+
+#[binding(bounds = Send + Sync)]
+impl Store for Repository {
+    fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+#[test]
+fn arcs_keep_their_dependencies_after_the_injector_is_dropped() {
+    let injector = Injector::new();
+    let repository = injector.get_arc::<Repository>();
+    let store = injector.get_trait_object_arc::<dyn Store + Send + Sync>();
+    assert!(Arc::ptr_eq(&repository.connection, &injector.get_arc::<Connection>()));
+    assert!(std::ptr::eq(store.connection(), &*repository.connection));
+
+    drop(injector);
+    // The store is the same repository, which is the only thing left holding the connection
+    assert_eq!(Arc::strong_count(&repository), 2);
+    assert_eq!(Arc::strong_count(&repository.connection), 1);
+}
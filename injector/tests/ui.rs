@@ -0,0 +1,10 @@
+// The diagnostics the derive macros give for invalid input, and inputs that they once got wrong.
+// Run with `TRYBUILD=overwrite` to update the expected output after changing a message.
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+    cases.pass("tests/ui/pass/*.rs");
+    #[cfg(not(feature = "config"))]
+    cases.compile_fail("tests/ui/without_config/*.rs");
+}
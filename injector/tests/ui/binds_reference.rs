@@ -0,0 +1,11 @@
+use injector::Injectable;
+
+trait Limits {}
+
+impl Limits for RateLimits {}
+
+#[derive(Injectable)]
+#[binds(&dyn Limits)]
+struct RateLimits;
+
+fn main() {}
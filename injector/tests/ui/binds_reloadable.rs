@@ -0,0 +1,15 @@
+use injector::Injectable;
+
+trait Limits {}
+
+impl Limits for RateLimits {}
+
+#[derive(Injectable)]
+#[reloadable(watch = "limits.toml")]
+#[binds(dyn Limits)]
+struct RateLimits {
+    #[inject(default)]
+    requests_per_second: u32,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use injector::Injectable;
+
+trait Principal {}
+
+impl Principal for User {}
+
+#[derive(Injectable)]
+#[injectable(scope = request)]
+#[binds(dyn Principal)]
+struct User;
+
+fn main() {}
@@ -0,0 +1,14 @@
+use std::sync::Arc;
+
+use injector::Injectable;
+
+#[derive(Injectable)]
+struct Connection;
+
+#[derive(Injectable)]
+struct Repository {
+    #[inject(clone)]
+    connection: Arc<Connection>,
+}
+
+fn main() {}
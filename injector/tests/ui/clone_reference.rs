@@ -0,0 +1,13 @@
+use injector::Injectable;
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Port(u16);
+
+#[derive(Injectable)]
+struct Server<'a> {
+    #[inject(clone)]
+    port: &'a Port,
+}
+
+fn main() {}
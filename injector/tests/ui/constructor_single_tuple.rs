@@ -0,0 +1,10 @@
+use injector::constructor;
+
+struct Port(u16);
+
+#[constructor]
+fn port() -> (Port,) {
+    (Port(8080),)
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use injector::constructor;
+
+struct Port(u16);
+
+#[constructor]
+impl Port {
+    #[constructor]
+    fn from_port(&self) -> Port {
+        Port(self.0)
+    }
+}
+
+fn main() {}
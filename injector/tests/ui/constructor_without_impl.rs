@@ -0,0 +1,12 @@
+use injector::constructor;
+
+struct Port(u16);
+
+impl Port {
+    #[constructor]
+    fn new() -> Self {
+        Port(8080)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use injector::constructor;
+
+struct Port(u16);
+
+#[constructor]
+impl Port {
+    #[constructor]
+    fn new() -> Option<Self> {
+        Some(Port(8080))
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use injector::Injectable;
+
+#[derive(Injectable)]
+struct Settings {
+    #[inject(env = "EXAMPLE_KEY", default = b"secret")]
+    key: String,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use injector::Injectable;
+
+#[derive(Injectable)]
+struct Parser<'a> {
+    parse: &'a (dyn for<'b> Fn(&'b str) -> u8 + Send),
+}
+
+fn main() {}
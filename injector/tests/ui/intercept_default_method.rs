@@ -0,0 +1,22 @@
+use injector::{Injectable, binding, interceptable};
+
+#[interceptable]
+trait Greeter {
+    fn greet(&self) -> String;
+
+    fn farewell(&self) -> String {
+        "goodbye".to_string()
+    }
+}
+
+#[derive(Injectable)]
+struct English;
+
+#[binding(intercept)]
+impl Greeter for English {
+    fn greet(&self) -> String {
+        "hello".to_string()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use injector::{Injectable, binding};
+
+trait Greeter {
+    fn greet(&self) -> String;
+}
+
+#[derive(Injectable)]
+struct English;
+
+#[binding(intercept)]
+impl Greeter for English {
+    fn greet(&self) -> String {
+        "hello".to_string()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+// Bindings for the same type and trait, that only differ in generic arguments that aren't paths,
+// must still get distinct names.
+use injector::{Injectable, Injector, binding};
+
+trait Parse<T> {
+    fn parse(&self) -> T;
+}
+
+#[derive(Injectable)]
+struct Parser;
+
+#[binding]
+impl Parse<&'static str> for Parser {
+    fn parse(&self) -> &'static str {
+        "text"
+    }
+}
+
+#[binding]
+impl Parse<[u8; 4]> for Parser {
+    fn parse(&self) -> [u8; 4] {
+        [1, 2, 3, 4]
+    }
+}
+
+#[binding]
+impl Parse<(u8, u8)> for Parser {
+    fn parse(&self) -> (u8, u8) {
+        (1, 2)
+    }
+}
+
+#[binding]
+impl Parse<fn() -> u8> for Parser {
+    fn parse(&self) -> fn() -> u8 {
+        || 1
+    }
+}
+
+fn main() {
+    let injector = Injector::new();
+    assert_eq!(injector.get_trait_object::<dyn Parse<&'static str>>().parse(), "text");
+    assert_eq!(injector.get_trait_object::<dyn Parse<[u8; 4]>>().parse(), [1, 2, 3, 4]);
+    assert_eq!(injector.get_trait_object::<dyn Parse<(u8, u8)>>().parse(), (1, 2));
+    assert_eq!(injector.get_trait_object::<dyn Parse<fn() -> u8>>().parse()(), 1);
+}
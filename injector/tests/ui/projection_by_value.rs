@@ -0,0 +1,15 @@
+use injector::Injectable;
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Config {
+    port: u16,
+}
+
+#[derive(Injectable)]
+struct Server {
+    #[inject(from = Config, path = port)]
+    port: u16,
+}
+
+fn main() {}
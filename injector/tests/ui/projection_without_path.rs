@@ -0,0 +1,15 @@
+use injector::Injectable;
+
+#[derive(Injectable)]
+#[has_constructor]
+struct Config {
+    port: u16,
+}
+
+#[derive(Injectable)]
+struct Server<'a> {
+    #[inject(from = Config)]
+    port: &'a u16,
+}
+
+fn main() {}
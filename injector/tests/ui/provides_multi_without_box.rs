@@ -0,0 +1,10 @@
+use injector::provides;
+
+struct Plugin;
+
+#[provides(multi)]
+fn make_plugin() -> Plugin {
+    Plugin
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use injector::provides;
+
+struct Port(u16);
+
+#[provides]
+fn port() -> (Port,) {
+    (Port(8080),)
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use injector::Injectable;
+
+#[derive(Injectable)]
+#[injectable(scope = request)]
+#[reloadable(watch = "limits.toml")]
+struct RateLimits {
+    #[inject(default)]
+    requests_per_second: u32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use injector::Injectable;
+
+#[derive(Injectable)]
+#[injectable(scope = session)]
+struct Cart;
+
+fn main() {}
@@ -0,0 +1,13 @@
+use injector::Injectable;
+
+fn greet(name: &str) -> String {
+    format!("hello {}", name)
+}
+
+#[derive(Injectable)]
+struct Greeting {
+    #[inject(with = greet)]
+    text: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use injector::Injectable;
+
+#[derive(Injectable, serde::Deserialize)]
+#[config(section = "http")]
+struct HttpConfig {
+    port: u16,
+}
+
+fn main() {}